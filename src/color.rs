@@ -0,0 +1,269 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use clap::ValueEnum;
+use crossterm::style::{Attribute, Color, ContentStyle};
+
+use crate::{
+    file::{File, FileType},
+    matcher::Matcher,
+};
+
+// Roughly what `dircolors --print-database` ships with
+const DEFAULT_LS_COLORS: &str = "di=01;34:ln=01;36:or=40;31;01:mi=01;31:pi=40;33:so=01;35:\
+bd=40;33;01:cd=40;33;01:ex=01;32:\
+*.tar=01;31:*.tgz=01;31:*.gz=01;31:*.bz2=01;31:*.xz=01;31:*.zst=01;31:*.zip=01;31:\
+*.7z=01;31:*.rar=01;31:*.deb=01;31:*.rpm=01;31:*.jar=01;31:\
+*.jpg=01;35:*.jpeg=01;35:*.png=01;35:*.gif=01;35:*.bmp=01;35:*.svg=01;35:*.webp=01;35:\
+*.mkv=01;35:*.mp4=01;35:*.mov=01;35:*.webm=01;35:*.avi=01;35:\
+*.flac=00;36:*.mp3=00;36:*.ogg=00;36:*.opus=00;36:*.wav=00;36:*.m4a=00;36";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// Color if stdout is a terminal and NO_COLOR is not set
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn enabled(self) -> bool {
+        self.enabled_on(&io::stdout())
+    }
+
+    /// Whether to color what is written to `stream`, which is checked for `Auto`
+    pub fn enabled_on(self, stream: &impl IsTerminal) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                // https://no-color.org
+                let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+                !no_color && stream.is_terminal()
+            }
+        }
+    }
+}
+
+/// Styles by file type and name suffix in the format of the `LS_COLORS` variable
+#[derive(Debug, Default, Clone)]
+pub struct LsColors {
+    directory: Option<ContentStyle>,
+    executable: Option<ContentStyle>,
+    file: Option<ContentStyle>,
+    symlink: Option<ContentStyle>,
+    /// `ln=target` colors links like the file they point to
+    symlink_as_target: bool,
+    orphan: Option<ContentStyle>,
+    missing: Option<ContentStyle>,
+    block_device: Option<ContentStyle>,
+    char_device: Option<ContentStyle>,
+    pipe: Option<ContentStyle>,
+    socket: Option<ContentStyle>,
+    suffixes: Vec<(String, ContentStyle)>,
+    /// Styles by name, which take precedence over everything else
+    overrides: Matcher<ContentStyle>,
+}
+
+impl LsColors {
+    /// Uses `LS_COLORS` if set and non-empty, the built-in defaults otherwise
+    pub fn from_env() -> Self {
+        match env::var("LS_COLORS") {
+            Ok(value) if !value.is_empty() => Self::parse(&value),
+            _ => Self::parse(DEFAULT_LS_COLORS),
+        }
+    }
+
+    pub fn parse(spec: &str) -> Self {
+        let mut colors = LsColors::default();
+
+        for (key, value) in spec.split(':').filter_map(|e| e.split_once('=')) {
+            if key == "ln" && value == "target" {
+                colors.symlink_as_target = true;
+                continue;
+            }
+
+            let style = parse_sgr(value);
+            match key {
+                "di" => colors.directory = style,
+                "ex" => colors.executable = style,
+                "fi" => colors.file = style,
+                "ln" => colors.symlink = style,
+                "or" => colors.orphan = style,
+                "mi" => colors.missing = style,
+                "bd" => colors.block_device = style,
+                "cd" => colors.char_device = style,
+                "pi" => colors.pipe = style,
+                "so" => colors.socket = style,
+                _ => {
+                    if let (Some(suffix), Some(style)) = (key.strip_prefix('*'), style) {
+                        colors.suffixes.push((suffix.to_owned(), style));
+                    }
+                }
+            }
+        }
+
+        colors
+    }
+
+    /// Adds styles by name, extension or glob which take precedence over the file type
+    pub fn with_overrides(mut self, overrides: Matcher<ContentStyle>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    /// The style for `file`, the file type takes precedence over the name
+    pub fn style(&self, file: &File) -> Option<ContentStyle> {
+        if let Some(&style) = self.overrides.get(&file.name_lossy()) {
+            return Some(style);
+        }
+        match file.ftype() {
+            FileType::File { exec: true } => {
+                self.executable.or_else(|| self.by_name(&file.name_lossy()))
+            }
+            FileType::File { .. } => self.by_name(&file.name_lossy()).or(self.file),
+            FileType::Directory => self.directory,
+            FileType::Symlink { valid: false, .. } => self.orphan.or(self.symlink),
+            FileType::Symlink { target, to_dir, .. } if self.symlink_as_target => {
+                self.target_style(&target.to_string_lossy(), *to_dir, true)
+            }
+            FileType::Symlink { .. } => self.symlink,
+            FileType::BlockDevice => self.block_device,
+            FileType::CharDevice => self.char_device,
+            FileType::Pipe => self.pipe,
+            FileType::Socket => self.socket,
+            FileType::Special => None,
+        }
+    }
+
+    /// The style for a symlink regardless of where it points
+    pub fn link_style(&self) -> Option<ContentStyle> {
+        self.symlink
+    }
+
+    /// The style for the target of a symlink
+    pub fn target_style(&self, target: &str, to_dir: bool, valid: bool) -> Option<ContentStyle> {
+        if !valid {
+            self.missing.or(self.orphan)
+        } else if to_dir {
+            self.directory
+        } else {
+            self.by_name(target).or(self.file)
+        }
+    }
+
+    /// The style of the longest matching suffix
+    fn by_name(&self, name: &str) -> Option<ContentStyle> {
+        self.suffixes
+            .iter()
+            .filter(|(suffix, _)| name.ends_with(suffix.as_str()))
+            .max_by_key(|(suffix, _)| suffix.len())
+            .map(|(_, style)| *style)
+    }
+}
+
+/// Converts a `;` separated list of SGR parameters into a style
+pub(crate) fn parse_sgr(sgr: &str) -> Option<ContentStyle> {
+    let mut style = ContentStyle::new();
+    let mut codes = sgr.split(';').map(|c| c.parse::<u8>().ok());
+
+    while let Some(code) = codes.next() {
+        match code? {
+            0 => {}
+            1 => style.attributes.set(Attribute::Bold),
+            2 => style.attributes.set(Attribute::Dim),
+            3 => style.attributes.set(Attribute::Italic),
+            4 => style.attributes.set(Attribute::Underlined),
+            5 => style.attributes.set(Attribute::SlowBlink),
+            7 => style.attributes.set(Attribute::Reverse),
+            8 => style.attributes.set(Attribute::Hidden),
+            9 => style.attributes.set(Attribute::CrossedOut),
+            n @ 30..=37 => style.foreground_color = Some(basic_color(n - 30)),
+            n @ 40..=47 => style.background_color = Some(basic_color(n - 40)),
+            n @ 90..=97 => style.foreground_color = Some(bright_color(n - 90)),
+            n @ 100..=107 => style.background_color = Some(bright_color(n - 100)),
+            n @ (38 | 48) => {
+                let color = match codes.next()?? {
+                    5 => Color::AnsiValue(codes.next()??),
+                    2 => Color::Rgb {
+                        r: codes.next()??,
+                        g: codes.next()??,
+                        b: codes.next()??,
+                    },
+                    _ => return None,
+                };
+                if n == 38 {
+                    style.foreground_color = Some(color);
+                } else {
+                    style.background_color = Some(color);
+                }
+            }
+            // Ignore what we cannot represent rather than dropping the whole style
+            _ => {}
+        }
+    }
+
+    Some(style)
+}
+
+fn basic_color(n: u8) -> Color {
+    match n {
+        0 => Color::Black,
+        1 => Color::DarkRed,
+        2 => Color::DarkGreen,
+        3 => Color::DarkYellow,
+        4 => Color::DarkBlue,
+        5 => Color::DarkMagenta,
+        6 => Color::DarkCyan,
+        _ => Color::Grey,
+    }
+}
+
+fn bright_color(n: u8) -> Color {
+    match n {
+        0 => Color::DarkGrey,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        _ => Color::White,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_sgr_test() {
+        let mut bold_blue = ContentStyle::new();
+        bold_blue.attributes.set(Attribute::Bold);
+        bold_blue.foreground_color = Some(Color::DarkBlue);
+        assert_eq!(parse_sgr("01;34"), Some(bold_blue));
+
+        let mut rgb_on_red = ContentStyle::new();
+        rgb_on_red.foreground_color = Some(Color::Rgb { r: 1, g: 2, b: 3 });
+        rgb_on_red.background_color = Some(Color::DarkRed);
+        assert_eq!(parse_sgr("38;2;1;2;3;41"), Some(rgb_on_red));
+
+        let mut indexed = ContentStyle::new();
+        indexed.foreground_color = Some(Color::AnsiValue(208));
+        assert_eq!(parse_sgr("38;5;208"), Some(indexed));
+
+        assert_eq!(parse_sgr("38;5"), None);
+        assert_eq!(parse_sgr("bogus"), None);
+    }
+
+    #[test]
+    fn parse_test() {
+        let colors = LsColors::parse("di=34:ln=target:*.tar.gz=31:*.gz=32:garbage");
+        assert_eq!(colors.directory, parse_sgr("34"));
+        assert!(colors.symlink_as_target);
+        assert_eq!(colors.by_name("a.tar.gz"), parse_sgr("31"));
+        assert_eq!(colors.by_name("a.gz"), parse_sgr("32"));
+        assert_eq!(colors.by_name("a.txt"), None);
+    }
+}
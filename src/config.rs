@@ -0,0 +1,342 @@
+use std::{env, ffi::OsString, iter, path::PathBuf};
+
+use clap::{
+    parser::ValueSource, value_parser, Arg, ArgAction, ArgMatches, Command, CommandFactory,
+    FromArgMatches, Parser,
+};
+
+use crate::{
+    color::{ColorChoice, LsColors},
+    details,
+    error::Error,
+    filter::Pattern,
+    icons::{IconSet, Icons},
+    render::Output,
+    settings::{Overrides, Settings},
+    sort::{DirOrder, SortBy, Sorter},
+};
+
+#[derive(Parser)]
+// Repeated options replace the earlier ones
+#[command(
+    author,
+    version,
+    about,
+    disable_help_flag = true,
+    args_override_self = true
+)]
+pub struct Config {
+    /// The directories or files to list, each as its own tree
+    #[arg(default_value = ".", value_name = "DIRECTORY")]
+    pub(crate) directories: Vec<PathBuf>,
+
+    /// Do not ignore entries starting with .
+    #[arg(short, long)]
+    pub(crate) all: bool,
+
+    /// Only display directories ignoring all files
+    #[arg(short, long)]
+    pub(crate) directory_only: bool,
+
+    /// The recursion depth
+    #[arg(short, long, default_value_t = 5)]
+    pub(crate) limit: u32,
+
+    /// Descend into symlinks to directories, except those leading back up
+    #[arg(long)]
+    pub(crate) follow: bool,
+
+    /// Show the canonical path symlinks finally lead to instead of each hop
+    #[arg(long)]
+    pub(crate) resolve: bool,
+
+    /// Hide entries ignored by .gitignore, .ignore and git's excludes,
+    /// the default inside a git work tree
+    #[arg(long, overrides_with = "no_gitignore")]
+    pub(crate) gitignore: bool,
+
+    /// Do not hide entries ignored by git
+    #[arg(long, overrides_with = "gitignore")]
+    pub(crate) no_gitignore: bool,
+
+    /// Only list files matching the glob
+    #[arg(short = 'P', long, value_name = "GLOB")]
+    pub(crate) include: Vec<Pattern>,
+
+    /// Do not list files or directories matching the glob
+    #[arg(short = 'I', long, value_name = "GLOB")]
+    pub(crate) exclude: Vec<Pattern>,
+
+    /// Also apply include patterns to directory names,
+    /// listing all contents of a matching directory
+    #[arg(long)]
+    pub(crate) matchdirs: bool,
+
+    /// Do not list directories which are empty after filtering
+    #[arg(long)]
+    pub(crate) prune: bool,
+
+    /// How to sort the entries of each directory
+    #[arg(long, value_enum, default_value_t = SortBy::Name)]
+    pub(crate) sort: SortBy,
+
+    /// Reverse the sort order
+    #[arg(short, long)]
+    pub(crate) reverse: bool,
+
+    /// List directories before files
+    #[arg(long, conflicts_with = "dirs_last")]
+    pub(crate) dirs_first: bool,
+
+    /// List directories after files
+    #[arg(long)]
+    pub(crate) dirs_last: bool,
+
+    /// Print the permissions of each entry, like `ls -l`
+    #[arg(short = 'p', long)]
+    pub(crate) permissions: bool,
+
+    /// Print the owner of each entry, by name if known
+    #[arg(short = 'u', long)]
+    pub(crate) user: bool,
+
+    /// Print the group of each entry, by name if known
+    #[arg(short = 'g', long)]
+    pub(crate) group: bool,
+
+    /// Print the size of each entry in bytes
+    #[arg(short = 's', long)]
+    pub(crate) size: bool,
+
+    /// Print sizes in human readable units of 1024
+    #[arg(short = 'h', long)]
+    pub(crate) human: bool,
+
+    /// Like --human, but with units of 1000
+    #[arg(long)]
+    pub(crate) si: bool,
+
+    /// Print the total size of their contents for directories
+    #[arg(long)]
+    pub(crate) du: bool,
+
+    /// Print the modification time of each entry
+    #[arg(short = 'D', long)]
+    pub(crate) date: bool,
+
+    /// The strftime format for the modification time, implies --date
+    #[arg(long, value_name = "FORMAT", value_parser = details::parse_time_format)]
+    pub(crate) timefmt: Option<String>,
+
+    /// Print the staged and unstaged git status of each entry, like `git status --short`
+    #[arg(long)]
+    pub(crate) git: bool,
+
+    /// When to color the output
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    pub(crate) color: ColorChoice,
+
+    /// Escape non-printable characters and invalid UTF-8 in names,
+    /// which are replaced by ? and � otherwise
+    #[arg(short = 'q', long)]
+    pub(crate) escape: bool,
+
+    /// Print non-printable characters in names as they are
+    #[arg(short = 'N', long, conflicts_with = "escape")]
+    pub(crate) literal: bool,
+
+    /// Print the path of each entry, starting with the directory argument
+    #[arg(short = 'f', long)]
+    pub(crate) full_path: bool,
+
+    /// Print the absolute canonical path of each entry
+    #[arg(long)]
+    pub(crate) absolute: bool,
+
+    /// Do not draw the tree, printing a flat list instead
+    #[arg(long)]
+    pub(crate) noindent: bool,
+
+    /// Which icons to prefix names with
+    #[arg(long, value_enum, default_value_t = IconSet::Nerd)]
+    pub(crate) icons: IconSet,
+
+    /// Shorthand for --icons=none
+    #[arg(long)]
+    pub(crate) no_icons: bool,
+
+    /// The output format
+    #[arg(long, value_enum, default_value_t = Output::Text)]
+    pub(crate) output: Output,
+
+    /// Prefix for the links in HTML output
+    #[arg(long)]
+    pub(crate) base_href: Option<String>,
+
+    /// Do not descend into directories with more than N entries
+    #[arg(long, value_name = "N")]
+    pub(crate) filelimit: Option<usize>,
+
+    /// Only list the first N entries of each directory and summarize the rest
+    #[arg(long, value_name = "N")]
+    pub(crate) max_entries: Option<usize>,
+
+    /// Write entries as they are read instead of sorting them, which keeps memory use low
+    /// for huge directories
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "sort", "reverse", "dirs_first", "dirs_last", "prune", "du", "filelimit", "max_entries"
+        ]
+    )]
+    pub(crate) unsorted: bool,
+
+    /// How many threads read directories, 0 for one per CPU
+    #[arg(long, default_value_t = 0)]
+    pub(crate) threads: usize,
+
+    /// Exit with a failure status if any directory or entry could not be read
+    #[arg(long)]
+    pub(crate) strict: bool,
+
+    /// Browse the tree in the terminal and print the selected path
+    #[arg(short, long, conflicts_with = "unsorted")]
+    pub(crate) interactive: bool,
+
+    /// Ignore the config file
+    #[arg(long)]
+    pub(crate) no_config: bool,
+
+    #[arg(skip)]
+    pub(crate) overrides: Overrides,
+
+    /// Print help
+    #[arg(long, action = ArgAction::Help)]
+    pub(crate) help: Option<bool>,
+}
+
+impl Config {
+    /// Parses the command line like [`Parser::parse`], with the options of the config file as
+    /// defaults unless `--no-config` is given
+    pub fn load() -> Result<Config, Error> {
+        let args: Vec<OsString> = env::args_os().collect();
+        // Help, the version and mistakes on the command line do not depend on the config file
+        let given = command()
+            .try_get_matches_from(&args)
+            .unwrap_or_else(|e| e.exit());
+        let settings = if given.get_flag("no_config") {
+            Settings::default()
+        } else {
+            Settings::load()?
+        };
+
+        Ok(Config::with_settings(&args, &given, settings).unwrap_or_else(|e| e.exit()))
+    }
+
+    /// Parses `args`, which were `given` on the command line, with the options of `settings`
+    /// which the command line neither repeats nor conflicts with in front of them
+    pub(crate) fn with_settings(
+        args: &[OsString],
+        given: &ArgMatches,
+        settings: Settings,
+    ) -> Result<Config, clap::Error> {
+        let command = command();
+        let given: Vec<&Arg> = command
+            .get_arguments()
+            .filter(|arg| {
+                given.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+            })
+            .collect();
+        let conflict = |a: &Arg, b: &Arg| {
+            a.get_id() == b.get_id()
+                || command.get_arg_conflicts_with(a).contains(&b)
+                || command.get_arg_conflicts_with(b).contains(&a)
+        };
+
+        let mut defaults = Vec::new();
+        for (name, option) in &settings.options {
+            let arg = command
+                .get_arguments()
+                .find(|arg| arg.get_long() == Some(name.as_str()));
+            if arg.is_some_and(|arg| !given.iter().any(|given| conflict(given, arg))) {
+                defaults.extend(option);
+            }
+        }
+
+        let args = iter::once(&args[0]).chain(defaults).chain(&args[1..]);
+        let matches = command.clone().try_get_matches_from(args)?;
+        let mut config = Config::from_arg_matches(&matches)?;
+        config.overrides = settings.overrides;
+        Ok(config)
+    }
+
+    /// Whether unreadable entries should make `mtree` fail
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Whether to browse the tree instead of printing it
+    pub fn interactive(&self) -> bool {
+        self.interactive
+    }
+
+    /// The icons to show, including the ones from the config file
+    pub(crate) fn icons(&self) -> Icons {
+        Icons {
+            set: if self.no_icons {
+                IconSet::None
+            } else {
+                self.icons
+            },
+            custom: self.overrides.icons.clone(),
+        }
+    }
+
+    /// The colors if `enabled`, including the ones from the config file
+    pub(crate) fn colors(&self, enabled: bool) -> Option<LsColors> {
+        enabled.then(|| LsColors::from_env().with_overrides(self.overrides.colors.clone()))
+    }
+
+    /// The order of the entries of each directory
+    pub(crate) fn sorter(&self) -> Sorter {
+        let dirs = if self.dirs_first {
+            DirOrder::First
+        } else if self.dirs_last {
+            DirOrder::Last
+        } else {
+            DirOrder::Mixed
+        };
+        Sorter::new(self.sort, self.reverse, dirs)
+    }
+}
+
+/// The command line interface, where flags also take `=true` or `=false` so that the command line
+/// can turn off the ones of the config file
+pub(crate) fn command() -> Command {
+    let command = Config::command();
+    let flags: Vec<_> = command
+        .get_arguments()
+        .filter(|arg| matches!(arg.get_action(), ArgAction::SetTrue))
+        .map(|arg| arg.get_id().clone())
+        .collect();
+    flags.into_iter().fold(command, |command, flag| {
+        command.mut_arg(flag, |arg| {
+            arg.action(ArgAction::Set)
+                .value_parser(value_parser!(bool))
+                .value_name("BOOL")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("true")
+                .default_value("false")
+                .hide_default_value(true)
+                .hide_possible_values(true)
+        })
+    })
+}
+
+impl Default for Config {
+    /// The options when none are given on the command line
+    fn default() -> Self {
+        Config::parse_from(["mtree"])
+    }
+}
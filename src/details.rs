@@ -0,0 +1,314 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Local,
+};
+
+use crate::{
+    file::{File, FileType},
+    git::GitStatus,
+};
+
+/// Used by `tree -D` for recent times
+const RECENT_TIME_FORMAT: &str = "%b %e %H:%M";
+/// Used by `tree -D` for times more than half a year away, which show the year instead
+const OLD_TIME_FORMAT: &str = "%b %e  %Y";
+const HALF_YEAR: Duration = Duration::from_secs(365 * 24 * 60 * 60 / 2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeUnits {
+    Bytes,
+    /// Powers of 1024
+    Iec,
+    /// Powers of 1000
+    Si,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// Like `ls -l`, the time for recent files and the year for older ones
+    Default,
+    /// A strftime format
+    Custom(String),
+}
+
+/// The bracketed columns in front of a name, like `[drwxr-xr-x  4.0K]  name` with `tree -ph`
+#[derive(Debug, Clone, Default)]
+pub struct Details {
+    pub permissions: bool,
+    pub users: Option<Names>,
+    pub groups: Option<Names>,
+    pub size: Option<SizeUnits>,
+    pub time: Option<TimeFormat>,
+    pub git: Option<GitStatus>,
+}
+
+impl Details {
+    pub fn is_empty(&self) -> bool {
+        !self.permissions
+            && self.users.is_none()
+            && self.groups.is_none()
+            && self.size.is_none()
+            && self.time.is_none()
+            && self.git.is_none()
+    }
+
+    /// The owner of `file` if owners are shown
+    pub fn user(&self, file: &File) -> Option<String> {
+        self.users.as_ref().map(|names| names.get(file.uid()))
+    }
+
+    /// The group of `file` if groups are shown
+    pub fn group(&self, file: &File) -> Option<String> {
+        self.groups.as_ref().map(|names| names.get(file.gid()))
+    }
+
+    /// The modification time of `file` if times are shown
+    pub fn time(&self, file: &File) -> Option<String> {
+        self.time
+            .as_ref()
+            .map(|format| format_time(file.modified(), format, SystemTime::now()))
+    }
+
+    /// The git status of `file` if it is shown and `file` is in a repository
+    pub fn git(&self, file: &File) -> Option<String> {
+        self.git
+            .as_ref()
+            .and_then(|git| git.get(file.path()))
+            .map(|status| status.to_string())
+    }
+
+    /// Writes the columns for `file` including the separating spaces, nothing if there are none
+    pub fn write(&self, writer: &mut dyn Write, file: &File) -> io::Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let mut columns = Vec::new();
+        if self.permissions {
+            columns.push(permissions(file));
+        }
+        if let Some(user) = self.user(file) {
+            columns.push(format!("{:<8}", user));
+        }
+        if let Some(group) = self.group(file) {
+            columns.push(format!("{:<8}", group));
+        }
+        if let Some(units) = self.size {
+            columns.push(format_size(file.size(), units));
+        }
+        if let Some(time) = self.time(file) {
+            columns.push(time);
+        }
+        if let Some(status) = self.git(file) {
+            columns.push(status);
+        }
+
+        write!(writer, "[{}]  ", columns.join(" "))
+    }
+}
+
+/// User or group names by id, from `/etc/passwd` or `/etc/group`
+#[derive(Debug, Clone, Default)]
+pub struct Names(HashMap<u32, String>);
+
+impl Names {
+    pub fn users() -> Self {
+        Self::load(Path::new("/etc/passwd"))
+    }
+
+    pub fn groups() -> Self {
+        Self::load(Path::new("/etc/group"))
+    }
+
+    /// A missing or unreadable database results in numeric ids only
+    fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    /// Parses lines of `name:password:id:...`, the first entry for an id wins
+    pub fn parse(content: &str) -> Self {
+        let mut names = HashMap::new();
+        for line in content.lines().filter(|l| !l.starts_with('#')) {
+            let mut fields = line.split(':');
+            if let (Some(name), Some(id)) = (fields.next(), fields.nth(1)) {
+                if let Ok(id) = id.parse() {
+                    names.entry(id).or_insert_with(|| name.to_owned());
+                }
+            }
+        }
+        Self(names)
+    }
+
+    /// The name for `id`, or the id itself if it has none
+    pub fn get(&self, id: u32) -> String {
+        self.0.get(&id).cloned().unwrap_or_else(|| id.to_string())
+    }
+}
+
+/// Validates a strftime format, for use as a clap value parser
+pub fn parse_time_format(format: &str) -> Result<String, String> {
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        Err(format!("invalid strftime format '{}'", format))
+    } else {
+        Ok(format.to_owned())
+    }
+}
+
+/// Formats `time` in the local time zone relative to `now`
+pub fn format_time(time: SystemTime, format: &TimeFormat, now: SystemTime) -> String {
+    let format = match format {
+        TimeFormat::Custom(format) => format.as_str(),
+        TimeFormat::Default => {
+            let distance = now
+                .duration_since(time)
+                .or_else(|_| time.duration_since(now))
+                .unwrap_or_default();
+            if distance < HALF_YEAR {
+                RECENT_TIME_FORMAT
+            } else {
+                OLD_TIME_FORMAT
+            }
+        }
+    };
+    DateTime::<Local>::from(time).format(format).to_string()
+}
+
+/// The mode in the style of `ls -l`, like `drwxr-xr-x`
+pub fn permissions(file: &File) -> String {
+    let kind = match file.ftype() {
+        FileType::File { .. } => '-',
+        FileType::Directory => 'd',
+        FileType::Symlink { .. } => 'l',
+        FileType::BlockDevice => 'b',
+        FileType::CharDevice => 'c',
+        FileType::Pipe => 'p',
+        FileType::Socket => 's',
+        FileType::Special => '?',
+    };
+    format!("{}{}", kind, mode_string(file.mode()))
+}
+
+/// The nine `rwx` characters of `mode`, with setuid, setgid and sticky bits folded in
+fn mode_string(mode: u32) -> String {
+    let bit = |mask: u32, c: char| if mode & mask != 0 { c } else { '-' };
+    // Special bits replace the execute character, in upper case if execute is not set
+    let exec = |exec_mask: u32, special_mask: u32, special: char| match (
+        mode & exec_mask != 0,
+        mode & special_mask != 0,
+    ) {
+        (true, true) => special,
+        (false, true) => special.to_ascii_uppercase(),
+        (true, false) => 'x',
+        (false, false) => '-',
+    };
+
+    [
+        bit(0o400, 'r'),
+        bit(0o200, 'w'),
+        exec(0o100, 0o4000, 's'),
+        bit(0o040, 'r'),
+        bit(0o020, 'w'),
+        exec(0o010, 0o2000, 's'),
+        bit(0o004, 'r'),
+        bit(0o002, 'w'),
+        exec(0o001, 0o1000, 't'),
+    ]
+    .iter()
+    .collect()
+}
+
+/// Formats like `tree -s`, `tree -h` and `tree --si` respectively
+pub fn format_size(size: u64, units: SizeUnits) -> String {
+    let (base, suffixes) = match units {
+        SizeUnits::Bytes => return format!("{:>11}", size),
+        SizeUnits::Iec => (1024.0, ['K', 'M', 'G', 'T', 'P', 'E']),
+        SizeUnits::Si => (1000.0, ['k', 'M', 'G', 'T', 'P', 'E']),
+    };
+
+    if (size as f64) < base {
+        return format!("{:>4}", size);
+    }
+
+    let mut value = size as f64 / base;
+    let mut suffix = suffixes[0];
+    for &next in &suffixes[1..] {
+        if value < base {
+            break;
+        }
+        value /= base;
+        suffix = next;
+    }
+
+    if value < 9.95 {
+        format!("{:>3.1}{}", value, suffix)
+    } else {
+        format!("{:>3.0}{}", value, suffix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn format_size_test() {
+        assert_eq!(format_size(4096, SizeUnits::Bytes), "       4096");
+        assert_eq!(format_size(0, SizeUnits::Iec), "   0");
+        assert_eq!(format_size(1023, SizeUnits::Iec), "1023");
+        assert_eq!(format_size(4096, SizeUnits::Iec), "4.0K");
+        assert_eq!(format_size(4096, SizeUnits::Si), "4.1k");
+        assert_eq!(format_size(123_456_789, SizeUnits::Iec), "118M");
+        assert_eq!(format_size(10_189, SizeUnits::Iec), " 10K");
+        assert_eq!(format_size(5 << 40, SizeUnits::Iec), "5.0T");
+    }
+
+    #[test]
+    fn mode_string_test() {
+        assert_eq!(mode_string(0o755), "rwxr-xr-x");
+        assert_eq!(mode_string(0o640), "rw-r-----");
+        assert_eq!(mode_string(0o4755), "rwsr-xr-x");
+        assert_eq!(mode_string(0o2745), "rwxr-Sr-x");
+        assert_eq!(mode_string(0o1777), "rwxrwxrwt");
+        assert_eq!(mode_string(0o1776), "rwxrwxrwT");
+    }
+
+    #[test]
+    fn names_test() {
+        let names =
+            Names::parse("# comment\nroot:x:0:0::/root:/bin/sh\nalso:x:0:0::/:/bin/sh\nbin:x:1:\n");
+        assert_eq!(names.get(0), "root");
+        assert_eq!(names.get(1), "bin");
+        assert_eq!(names.get(1000), "1000");
+    }
+
+    #[test]
+    fn time_format_test() {
+        assert!(parse_time_format("%Y-%m-%d %H:%M").is_ok());
+        assert!(parse_time_format("%Q").is_err());
+
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let recent = format_time(now - Duration::from_secs(60), &TimeFormat::Default, now);
+        assert!(recent.contains(':'), "{}", recent);
+        let old = format_time(now - 2 * HALF_YEAR, &TimeFormat::Default, now);
+        assert!(!old.contains(':'), "{}", old);
+        assert_eq!(
+            format_time(
+                SystemTime::UNIX_EPOCH,
+                &TimeFormat::Custom("%%".into()),
+                now
+            ),
+            "%"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use std::{fmt::Display, io, path::PathBuf};
+
+/// What can go wrong while listing a tree
+#[derive(Debug)]
+pub enum Error {
+    /// A directory could not be listed, shown below it while the walk continues
+    ReadDir { source: io::Error },
+    /// The metadata of an entry could not be read
+    Metadata { path: PathBuf, source: io::Error },
+    /// A root given on the command line could not be read
+    Root { path: PathBuf, source: io::Error },
+    /// A followed symlink leads back to a directory being listed, shown instead of its contents
+    Recursive,
+    /// A directory with more entries than `--filelimit` allows was not opened
+    FileLimit(usize),
+    /// Writing the output failed, which ends the walk
+    Output(io::Error),
+    /// The config file could not be read or is invalid
+    Config { path: PathBuf, message: String },
+}
+
+impl Error {
+    pub fn read_dir(source: io::Error) -> Self {
+        Error::ReadDir { source }
+    }
+
+    pub fn metadata(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Error::Metadata {
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub fn root(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Error::Root {
+            path: path.into(),
+            source,
+        }
+    }
+
+    /// Whether this is an actual problem rather than a notice about what was not listed
+    pub fn is_failure(&self) -> bool {
+        !matches!(self, Error::Recursive | Error::FileLimit(_))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Output(error)
+    }
+}
+
+/// The message of `error` without the ` (os error 13)` suffix
+pub(crate) fn describe(error: &io::Error) -> String {
+    let message = error.to_string();
+    match message.find(" (os error") {
+        Some(end) => message[..end].to_owned(),
+        None => message,
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ReadDir { source, .. } => write!(f, "error opening dir: {}", describe(source)),
+            Error::Metadata { path, source } => {
+                let name = path.file_name().unwrap_or(path.as_os_str());
+                write!(
+                    f,
+                    "error reading {}: {}",
+                    name.to_string_lossy(),
+                    describe(source)
+                )
+            }
+            // Roots are shown as given, like in their header line
+            Error::Root { path, source } => {
+                write!(
+                    f,
+                    "error reading {}: {}",
+                    path.to_string_lossy(),
+                    describe(source)
+                )
+            }
+            Error::Recursive => write!(f, "recursive, not followed"),
+            Error::FileLimit(entries) => {
+                write!(f, "{} entries exceeds filelimit, not opening dir", entries)
+            }
+            Error::Output(source) => write!(f, "error writing output: {}", describe(source)),
+            Error::Config { path, message } => {
+                write!(f, "error in config file {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ReadDir { source, .. }
+            | Error::Metadata { source, .. }
+            | Error::Root { source, .. }
+            | Error::Output(source) => Some(source),
+            Error::Recursive | Error::FileLimit(_) | Error::Config { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn display_test() {
+        let denied = io::Error::from_raw_os_error(13);
+        assert_eq!(
+            Error::read_dir(denied).to_string(),
+            "error opening dir: Permission denied"
+        );
+
+        let missing = io::Error::new(io::ErrorKind::NotFound, "gone");
+        assert_eq!(
+            Error::metadata("a/b", missing).to_string(),
+            "error reading b: gone"
+        );
+        let missing = io::Error::new(io::ErrorKind::NotFound, "gone");
+        assert_eq!(
+            Error::root("a/b", missing).to_string(),
+            "error reading a/b: gone"
+        );
+        assert!(!Error::FileLimit(3).is_failure());
+    }
+}
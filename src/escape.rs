@@ -0,0 +1,64 @@
+use std::{borrow::Cow, ffi::OsStr, fmt::Write, os::unix::ffi::OsStrExt};
+
+/// Makes `s` printable with backslash escapes, like `ls -b`
+///
+/// Backslashes are doubled, common control characters become `\t`, `\n` and so on, other
+/// non-printable characters `\u{..}` and bytes which are not valid UTF-8 `\xNN`.
+pub fn escape(s: &OsStr) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for chunk in s.as_bytes().utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                '\t' => escaped.push_str("\\t"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\x1b' => escaped.push_str("\\e"),
+                c if c.is_control() => {
+                    let _ = write!(escaped, "\\u{{{:x}}}", c as u32);
+                }
+                c => escaped.push(c),
+            }
+        }
+        for &b in chunk.invalid() {
+            let _ = write!(escaped, "\\x{:02x}", b);
+        }
+    }
+    escaped
+}
+
+/// Makes `s` printable by replacing non-printable characters with `?` and invalid UTF-8 with `�`,
+/// like `tree` without `-N`
+pub fn sanitize(s: &OsStr) -> Cow<'_, str> {
+    match s.to_string_lossy() {
+        Cow::Borrowed(s) if !s.chars().any(char::is_control) => Cow::Borrowed(s),
+        s => Cow::Owned(
+            s.chars()
+                .map(|c| if c.is_control() { '?' } else { c })
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn escape_test() {
+        assert_eq!(escape(OsStr::new("plain ä.txt")), "plain ä.txt");
+        assert_eq!(
+            escape(OsStr::new("a\tb\nc\\d\x1b\x7f")),
+            "a\\tb\\nc\\\\d\\e\\u{7f}"
+        );
+        assert_eq!(escape(OsStr::from_bytes(b"caf\xe9\xff")), "caf\\xe9\\xff");
+    }
+
+    #[test]
+    fn sanitize_test() {
+        assert_eq!(sanitize(OsStr::new("plain ä.txt")), "plain ä.txt");
+        assert_eq!(sanitize(OsStr::new("a\x1b[2Jb\nc\x7f")), "a?[2Jb?c?");
+        assert_eq!(sanitize(OsStr::from_bytes(b"caf\xe9\t")), "caf�?");
+    }
+}
@@ -0,0 +1,90 @@
+use std::{ffi::OsStr, str::FromStr};
+
+use globset::{Glob, GlobMatcher};
+
+/// A glob matched against file names, `|` separates alternatives like in `tree -P`
+#[derive(Debug, Clone)]
+pub struct Pattern(Vec<GlobMatcher>);
+
+impl Pattern {
+    pub fn is_match(&self, name: &OsStr) -> bool {
+        self.0.iter().any(|glob| glob.is_match(name))
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = globset::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split('|')
+            .map(|alt| Glob::new(alt).map(|glob| glob.compile_matcher()))
+            .collect::<Result<_, _>>()
+            .map(Pattern)
+    }
+}
+
+/// The include (`-P`) and exclude (`-I`) patterns
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    /// Whether include patterns apply to directory names
+    match_dirs: bool,
+}
+
+impl Filter {
+    pub fn new(include: Vec<Pattern>, exclude: Vec<Pattern>, match_dirs: bool) -> Self {
+        Self {
+            include,
+            exclude,
+            match_dirs,
+        }
+    }
+
+    /// Whether `name` is pruned by an exclude pattern, which applies to files and directories
+    pub fn excludes(&self, name: &OsStr) -> bool {
+        self.exclude.iter().any(|p| p.is_match(name))
+    }
+
+    /// Whether a file called `name` is listed, directories are always listed
+    pub fn includes(&self, name: &OsStr, is_dir: bool) -> bool {
+        is_dir || self.include.is_empty() || self.include.iter().any(|p| p.is_match(name))
+    }
+
+    /// Whether a directory's name matches, which lists all its contents regardless of patterns
+    pub fn includes_dir(&self, name: &OsStr) -> bool {
+        self.match_dirs && self.include.iter().any(|p| p.is_match(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(patterns: &[&str]) -> Vec<Pattern> {
+        patterns.iter().map(|p| p.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn pattern_test() {
+        let pattern: Pattern = "*.rs|Cargo.*".parse().unwrap();
+        assert!(pattern.is_match(OsStr::new("main.rs")));
+        assert!(pattern.is_match(OsStr::new("Cargo.toml")));
+        assert!(!pattern.is_match(OsStr::new("main.c")));
+        assert!("[a-".parse::<Pattern>().is_err());
+    }
+
+    #[test]
+    fn filter_test() {
+        let filter = Filter::new(patterns(&["*.txt"]), patterns(&["d*"]), false);
+        assert!(filter.includes(OsStr::new("c.txt"), false));
+        assert!(!filter.includes(OsStr::new("c.md"), false));
+        assert!(filter.includes(OsStr::new("src"), true));
+        assert!(filter.excludes(OsStr::new("d.txt")));
+        assert!(filter.excludes(OsStr::new("dir")));
+        assert!(!filter.includes_dir(OsStr::new("c.txt")));
+
+        let filter = Filter::new(patterns(&["sr?"]), vec![], true);
+        assert!(filter.includes_dir(OsStr::new("src")));
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    fmt::{self, Display},
+    fs,
+    ops::Bound,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use git2::{Repository, StatusOptions};
+
+/// One column of `git status --short`
+///
+/// The order is the precedence when a directory sums up the changes below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Change {
+    Unchanged,
+    Ignored,
+    Untracked,
+    TypeChange,
+    Renamed,
+    Deleted,
+    Added,
+    Modified,
+    Conflicted,
+}
+
+impl Change {
+    pub fn symbol(self) -> char {
+        match self {
+            Change::Unchanged => '-',
+            Change::Ignored => '!',
+            Change::Untracked => '?',
+            Change::TypeChange => 'T',
+            Change::Renamed => 'R',
+            Change::Deleted => 'D',
+            Change::Added => 'A',
+            Change::Modified => 'M',
+            Change::Conflicted => 'U',
+        }
+    }
+}
+
+/// The staged and the unstaged change of an entry, like `git status --short` with `-` for none
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    pub staged: Change,
+    pub unstaged: Change,
+}
+
+impl Status {
+    pub const CLEAN: Status = Status {
+        staged: Change::Unchanged,
+        unstaged: Change::Unchanged,
+    };
+
+    fn both(change: Change) -> Self {
+        Status {
+            staged: change,
+            unstaged: change,
+        }
+    }
+
+    /// The more important change of each column
+    fn merge(self, other: Status) -> Self {
+        Status {
+            staged: self.staged.max(other.staged),
+            unstaged: self.unstaged.max(other.unstaged),
+        }
+    }
+}
+
+impl From<git2::Status> for Status {
+    fn from(flags: git2::Status) -> Self {
+        use git2::Status as S;
+
+        if flags.is_conflicted() {
+            return Status::both(Change::Conflicted);
+        }
+        if flags.is_ignored() {
+            return Status::both(Change::Ignored);
+        }
+        if flags.is_wt_new() {
+            return Status::both(Change::Untracked);
+        }
+
+        let column = |flags: S, new, modified, deleted, renamed, typechange| {
+            [
+                (new, Change::Added),
+                (modified, Change::Modified),
+                (deleted, Change::Deleted),
+                (renamed, Change::Renamed),
+                (typechange, Change::TypeChange),
+            ]
+            .into_iter()
+            .find(|&(flag, _)| flags.contains(flag))
+            .map_or(Change::Unchanged, |(_, change)| change)
+        };
+        Status {
+            staged: column(
+                flags,
+                S::INDEX_NEW,
+                S::INDEX_MODIFIED,
+                S::INDEX_DELETED,
+                S::INDEX_RENAMED,
+                S::INDEX_TYPECHANGE,
+            ),
+            unstaged: column(
+                flags,
+                S::WT_NEW,
+                S::WT_MODIFIED,
+                S::WT_DELETED,
+                S::WT_RENAMED,
+                S::WT_TYPECHANGE,
+            ),
+        }
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.staged.symbol(), self.unstaged.symbol())
+    }
+}
+
+/// A root of the listing inside a work tree
+#[derive(Debug, Clone)]
+struct Root {
+    path: PathBuf,
+    /// Where the root is relative to the work tree
+    prefix: PathBuf,
+    repo: usize,
+}
+
+/// The status of every changed, untracked or ignored entry in the repositories of the roots
+///
+/// Everything is read up front, from the index and the work tree, so looking up entries is cheap.
+#[derive(Debug, Clone, Default)]
+pub struct GitStatus {
+    roots: Vec<Root>,
+    /// The work tree and the status of its entries by relative path, for each repository
+    repos: Vec<(PathBuf, BTreeMap<PathBuf, Status>)>,
+}
+
+impl GitStatus {
+    /// Reads the repositories of `roots`, roots outside of any work tree have no status
+    pub fn new(roots: &[PathBuf]) -> Self {
+        let mut status = GitStatus::default();
+        for root in roots {
+            let Some((repo, workdir, prefix)) = open(root) else {
+                continue;
+            };
+            let repo = match status.repos.iter().position(|(w, _)| *w == workdir) {
+                Some(i) => i,
+                None => match statuses(&repo) {
+                    Ok(statuses) => {
+                        status.repos.push((workdir, statuses));
+                        status.repos.len() - 1
+                    }
+                    Err(_) => continue,
+                },
+            };
+            status.roots.push(Root {
+                path: root.clone(),
+                prefix,
+                repo,
+            });
+        }
+        status
+    }
+
+    /// The status of the entry at `path` below one of the roots
+    ///
+    /// Untracked and ignored directories are reported as a whole, so their contents share their
+    /// status. Other directories show the most important change of the entries below them,
+    /// where untracked entries only count for the work tree and ignored ones not at all.
+    pub fn get(&self, path: &Path) -> Option<Status> {
+        let root = self
+            .roots
+            .iter()
+            .filter(|root| path.starts_with(&root.path))
+            .max_by_key(|root| root.path.components().count())?;
+        let path = root.prefix.join(path.strip_prefix(&root.path).ok()?);
+        let statuses = &self.repos[root.repo].1;
+
+        if let Some(status) = path.ancestors().find_map(|a| statuses.get(a)) {
+            return Some(*status);
+        }
+
+        let below = statuses
+            .range::<Path, _>((Bound::Excluded(path.as_path()), Bound::Unbounded))
+            .take_while(|(p, _)| p.starts_with(&path))
+            .map(|(_, &status)| status);
+        let status = below.fold(Status::CLEAN, |sum, status| match status.unstaged {
+            Change::Ignored => sum,
+            Change::Untracked => sum.merge(Status {
+                staged: Change::Unchanged,
+                ..status
+            }),
+            _ => sum.merge(status),
+        });
+        Some(status)
+    }
+}
+
+/// The repository containing `root`, its canonical work tree and where `root` is in it
+fn open(root: &Path) -> Option<(Repository, PathBuf, PathBuf)> {
+    let dir = match root.parent() {
+        _ if root.is_dir() => root,
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let repo = Repository::discover(dir).ok()?;
+    let workdir = fs::canonicalize(repo.workdir()?).ok()?;
+    let prefix = fs::canonicalize(root)
+        .ok()?
+        .strip_prefix(&workdir)
+        .ok()?
+        .to_path_buf();
+    Some((repo, workdir, prefix))
+}
+
+/// Compares the index with `HEAD` and the work tree with the index, like `git status`
+fn statuses(repo: &Repository) -> Result<BTreeMap<PathBuf, Status>, git2::Error> {
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .include_ignored(true)
+        .recurse_ignored_dirs(false);
+
+    let statuses = repo.statuses(Some(&mut options))?;
+    Ok(statuses
+        .iter()
+        .map(|entry| {
+            let path = PathBuf::from(OsStr::from_bytes(entry.path_bytes()));
+            (path, Status::from(entry.status()))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+    use pretty_assertions::assert_eq;
+    use std::slice;
+
+    fn commit(repo: &Repository, paths: &[&str]) {
+        let mut index = repo.index().unwrap();
+        for path in paths {
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("mtree", "mtree@example.com").unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "test", &tree, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn status_test() {
+        let tmp = TempDir::new("git");
+        let dir = tmp.path();
+        let write = |path: &str, content: &str| tmp.file(path, content);
+
+        let repo = Repository::init(dir).unwrap();
+        write(".gitignore", "*.log\n");
+        write("clean.txt", "clean");
+        write("src/changed.rs", "old");
+        write("src/staged.rs", "old");
+        commit(
+            &repo,
+            &[".gitignore", "clean.txt", "src/changed.rs", "src/staged.rs"],
+        );
+
+        write("src/changed.rs", "new");
+        write("src/staged.rs", "new");
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("src/staged.rs")).unwrap();
+        index.write().unwrap();
+        write("src/new.rs", "");
+        write("new/a.txt", "");
+        write("debug.log", "");
+
+        let root = dir.join(".");
+        let status = GitStatus::new(slice::from_ref(&root));
+        let get = |path: &str| status.get(&root.join(path)).map(|s| s.to_string());
+        assert_eq!(get("clean.txt").as_deref(), Some("--"));
+        assert_eq!(get("src/changed.rs").as_deref(), Some("-M"));
+        assert_eq!(get("src/staged.rs").as_deref(), Some("M-"));
+        assert_eq!(get("src/new.rs").as_deref(), Some("??"));
+        assert_eq!(get("new").as_deref(), Some("??"));
+        assert_eq!(get("new/a.txt").as_deref(), Some("??"));
+        assert_eq!(get("debug.log").as_deref(), Some("!!"));
+        assert_eq!(get("src").as_deref(), Some("MM"));
+        assert_eq!(get("").as_deref(), Some("MM"));
+
+        // A root below the work tree
+        let src = dir.join("src");
+        let status = GitStatus::new(slice::from_ref(&src));
+        assert_eq!(
+            status.get(&src.join("changed.rs")),
+            Some(Status {
+                staged: Change::Unchanged,
+                unstaged: Change::Modified,
+            })
+        );
+        assert_eq!(status.get(Path::new("/elsewhere")), None);
+    }
+}
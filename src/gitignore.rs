@@ -0,0 +1,114 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    Match,
+};
+
+/// Per directory ignore files, earlier ones take precedence
+const IGNORE_FILES: [&str; 2] = [".ignore", ".gitignore"];
+
+/// The ignore rules in effect for a directory
+///
+/// Every directory the walk descends into gets a child of its parent's rules, so rules of deeper
+/// directories are consulted first and can override (or negate) the ones above.
+pub struct GitIgnore<'a> {
+    parent: Option<&'a GitIgnore<'a>>,
+    /// The absolute path of the directory
+    dir: PathBuf,
+    matchers: Vec<Gitignore>,
+}
+
+impl GitIgnore<'static> {
+    /// The rules for `dir` and everything that applies to it from further up
+    ///
+    /// These are the ignore files of `dir` and its ancestors up to the root of the work tree,
+    /// `.git/info/exclude` and git's `core.excludesFile`. Returns `None` if `require_git` is set
+    /// and `dir` is not inside a git work tree.
+    pub fn new(dir: &Path, require_git: bool) -> Option<Self> {
+        let dir = fs::canonicalize(dir).ok()?;
+        let work_tree = dir.ancestors().find(|d| d.join(".git").exists());
+        if require_git && work_tree.is_none() {
+            return None;
+        }
+
+        let mut matchers = Vec::new();
+        for ancestor in dir.ancestors() {
+            matchers.extend(from_dir(ancestor));
+            if Some(ancestor) == work_tree {
+                break;
+            }
+        }
+
+        let root = work_tree.unwrap_or(&dir);
+        if let Some(git_dir) = work_tree.and_then(git_dir) {
+            let mut builder = GitignoreBuilder::new(root);
+            builder.add(git_dir.join("info").join("exclude"));
+            matchers.extend(builder.build().ok());
+        }
+        let (global, _) = GitignoreBuilder::new(root).build_global();
+        matchers.push(global);
+
+        Some(GitIgnore {
+            parent: None,
+            dir,
+            matchers,
+        })
+    }
+}
+
+impl<'a> GitIgnore<'a> {
+    /// The rules for the subdirectory `name`
+    pub fn child(&'a self, name: &OsStr) -> GitIgnore<'a> {
+        let dir = self.dir.join(name);
+        GitIgnore {
+            parent: Some(self),
+            matchers: from_dir(&dir).collect(),
+            dir,
+        }
+    }
+
+    /// Whether the entry `name` of this directory is ignored
+    pub fn is_ignored(&self, name: &OsStr, is_dir: bool) -> bool {
+        let path = self.dir.join(name);
+
+        let mut current = Some(self);
+        while let Some(rules) = current {
+            for matcher in &rules.matchers {
+                match matcher.matched(&path, is_dir) {
+                    Match::Ignore(_) => return true,
+                    Match::Whitelist(_) => return false,
+                    Match::None => {}
+                }
+            }
+            current = rules.parent;
+        }
+
+        false
+    }
+}
+
+/// Matchers for the ignore files within `dir`
+fn from_dir(dir: &Path) -> impl Iterator<Item = Gitignore> + '_ {
+    IGNORE_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|path| path.is_file())
+        .map(|path| Gitignore::new(path).0)
+}
+
+/// The git directory of `work_tree`, following `gitdir:` files of worktrees and submodules
+fn git_dir(work_tree: &Path) -> Option<PathBuf> {
+    let dot_git = work_tree.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+
+    let content = fs::read_to_string(&dot_git).ok()?;
+    let target = content.strip_prefix("gitdir:")?.trim();
+    Some(work_tree.join(target))
+}
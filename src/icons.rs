@@ -0,0 +1,958 @@
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+use globset::GlobMatcher;
+use phf::phf_map;
+
+use crate::{
+    file::FileType,
+    matcher::{self, Matcher},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum IconSet {
+    /// Glyphs from a Nerd Font
+    Nerd,
+    /// Emoji, available in most terminal fonts
+    Emoji,
+    /// Plain ASCII markers for the file type
+    Ascii,
+    /// No icons at all
+    None,
+}
+
+/// An icon set with user-defined icons for names, extensions or globs layered over it
+#[derive(Debug, Clone)]
+pub struct Icons {
+    pub set: IconSet,
+    /// Used with every set but [`IconSet::None`]
+    pub custom: Matcher<String>,
+}
+
+impl From<IconSet> for Icons {
+    fn from(set: IconSet) -> Self {
+        Icons {
+            set,
+            custom: Matcher::default(),
+        }
+    }
+}
+
+impl Icons {
+    /// The user-defined icon for `name` if there is one, the one of the set otherwise
+    pub fn icon<'a>(&'a self, name: &str, ftype: &FileType) -> Option<&'a str> {
+        if self.set == IconSet::None {
+            return None;
+        }
+        match self.custom.get(name) {
+            Some(icon) => Some(icon),
+            None => self.set.icon(name, ftype),
+        }
+    }
+}
+
+/// Icons by lowercase names and extensions, looked up with [`matcher::find`]
+struct IconTable {
+    /// Names of anything but directories
+    by_name: &'static phf::Map<&'static str, &'static str>,
+    /// Names of directories, which have no extensions or globs
+    by_dir_name: &'static phf::Map<&'static str, &'static str>,
+    by_extension: &'static phf::Map<&'static str, &'static str>,
+    /// Lowercase patterns, which match regardless of case
+    by_glob: &'static [(&'static str, &'static str)],
+    by_type: fn(&FileType) -> &'static str,
+    /// `by_glob` compiled on first use
+    globs: OnceLock<Vec<(GlobMatcher, &'static str)>>,
+}
+
+impl IconTable {
+    fn glob(&self, name: &str) -> Option<&'static str> {
+        let globs = self.globs.get_or_init(|| {
+            self.by_glob
+                .iter()
+                .map(|&(glob, icon)| {
+                    let glob = matcher::glob_matcher(glob).expect("Invalid built-in glob");
+                    (glob, icon)
+                })
+                .collect()
+        });
+        globs
+            .iter()
+            .find(|(glob, _)| glob.is_match(name))
+            .map(|&(_, icon)| icon)
+    }
+}
+
+static NERD: IconTable = IconTable {
+    by_name: &ICONS_BY_NAME,
+    by_dir_name: &ICONS_BY_DIR_NAME,
+    by_extension: &ICONS_BY_EXTENSION,
+    by_glob: ICONS_BY_GLOB,
+    by_type: icons_by_type,
+    globs: OnceLock::new(),
+};
+
+static EMOJI: IconTable = IconTable {
+    by_name: &EMOJI_BY_NAME,
+    by_dir_name: &EMOJI_BY_DIR_NAME,
+    by_extension: &EMOJI_BY_EXTENSION,
+    by_glob: EMOJI_BY_GLOB,
+    by_type: emoji_by_type,
+    globs: OnceLock::new(),
+};
+
+static ASCII: IconTable = IconTable {
+    by_name: &phf_map! {},
+    by_dir_name: &phf_map! {},
+    by_extension: &phf_map! {},
+    by_glob: &[],
+    by_type: ascii_by_type,
+    globs: OnceLock::new(),
+};
+
+impl IconSet {
+    fn table(self) -> Option<&'static IconTable> {
+        match self {
+            IconSet::Nerd => Some(&NERD),
+            IconSet::Emoji => Some(&EMOJI),
+            IconSet::Ascii => Some(&ASCII),
+            IconSet::None => None,
+        }
+    }
+
+    /// The icon for `name` in the order of [`matcher::find`], or else by file type
+    ///
+    /// Directories and links to them are only looked up by name, in a table of their own, so a
+    /// `lib` directory and a file called `lib` get different icons.
+    pub fn icon(self, name: &str, ftype: &FileType) -> Option<&'static str> {
+        let table = self.table()?;
+        let icon = match ftype {
+            FileType::Directory | FileType::Symlink { to_dir: true, .. } => {
+                matcher::find_name(name, |name| table.by_dir_name.get(name).copied())
+            }
+            _ => matcher::find(
+                name,
+                |name| table.by_name.get(name).copied(),
+                |extension| table.by_extension.get(extension).copied(),
+                || table.glob(name),
+            ),
+        };
+        Some(icon.unwrap_or_else(|| (table.by_type)(ftype)))
+    }
+}
+
+fn icons_by_type(ftype: &FileType) -> &'static str {
+    match ftype {
+        FileType::File { exec } if *exec => "\u{f489}", //""
+        FileType::File { .. } => "\u{f016}",            // 
+        FileType::Directory => "\u{f115}",              // 
+        FileType::Symlink { to_dir, .. } if *to_dir => "\u{f482}", // 
+        FileType::Symlink { .. } => "\u{f481}",         // 
+        FileType::BlockDevice => "\u{fc29}",            // ﰩ
+        FileType::CharDevice => "\u{e601}",             // 
+        FileType::Pipe => "\u{f731}",                   // 
+        FileType::Socket => "\u{f6a7}",                 // 
+        FileType::Special => "\u{f2dc}",                // 
+    }
+}
+
+fn emoji_by_type(ftype: &FileType) -> &'static str {
+    match ftype {
+        FileType::File { exec } if *exec => "🔧",
+        FileType::File { .. } => "📄",
+        FileType::Directory => "📁",
+        FileType::Symlink { to_dir, .. } if *to_dir => "📂",
+        FileType::Symlink { .. } => "🔗",
+        FileType::BlockDevice => "💽",
+        FileType::CharDevice => "📟",
+        FileType::Pipe => "🔀",
+        FileType::Socket => "🔌",
+        FileType::Special => "❓",
+    }
+}
+
+fn ascii_by_type(ftype: &FileType) -> &'static str {
+    match ftype {
+        FileType::File { exec } if *exec => "*",
+        FileType::File { .. } => "-",
+        FileType::Directory => "+",
+        FileType::Symlink { .. } => "@",
+        FileType::BlockDevice => "b",
+        FileType::CharDevice => "c",
+        FileType::Pipe => "|",
+        FileType::Socket => "=",
+        FileType::Special => "?",
+    }
+}
+
+const ICONS_BY_NAME: phf::Map<&'static str, &'static str> = phf_map! {
+    "a.out" => "\u{f489}",              // ""
+    ".atom" => "\u{e764}",              // ""
+    "authorized_keys" => "\u{e60a}",    // ""
+    ".bash_logout" => "\u{e615}",       // ""
+    ".bash_profile" => "\u{e615}",      // ""
+    ".bashrc" => "\u{f489}",            // ""
+    ".bpython_history" => "\u{e606}",   // ""
+    "bspwmrc" => "\u{e615}",            // ""
+    "cargo.lock" => "\u{e7a8}",         // ""
+    "cargo.toml" => "\u{e7a8}",         // ""
+    "changelog" => "\u{e609}",          // ""
+    ".clang-format" => "\u{e615}",      // ""
+    "cmakelists.txt" => "\u{e615}",     // ""
+    "composer.json" => "\u{e608}",      // ""
+    "config" => "\u{e615}",             // ""
+    "config.ac" => "\u{e615}",          // ""
+    "config.el" => "\u{e779}",          // ""
+    "config.mk" => "\u{e615}",          // ""
+    "contributing" => "\u{e60a}",       // ""
+    "copyright" => "\u{e60a}",          // ""
+    "crontab" => "\u{e615}",            // ""
+    "crypttab" => "\u{e615}",           // ""
+    "custom.el" => "\u{e779}",          // ""
+    "docker-compose.yml" => "\u{f308}", // ""
+    "dockerfile" => "\u{f308}",         // ""
+    ".ds_store" => "\u{f179}",          // ""
+    ".editorconfig" => "\u{e615}",      // ""
+    ".env" => "\u{f462}",               // ""
+    ".eslintrc.json" => "\u{f462}",     // ""
+    ".eslintrc.js" => "\u{f462}",       // ""
+    ".eslintrc.yml" => "\u{f462}",      // ""
+    "favicon.ico" => "\u{f005}",        // ""
+    "fstab" => "\u{f1c0}",              // ""
+    ".gitattributes" => "\u{f1d3}",     // ""
+    ".gitconfig" => "\u{f1d3}",         // ""
+    ".git-credentials" => "\u{e60a}",   // ""
+    "gitignore_global" => "\u{f1d3}",   // ""
+    ".gitignore" => "\u{f1d3}",         // ""
+    ".gitlab-ci.yml" => "\u{f296}",     // ""
+    ".gitmodules" => "\u{f1d3}",        // ""
+    "group" => "\u{e615}",              // ""
+    "gruntfile.coffee" => "\u{e611}",   // ""
+    "gruntfile.js" => "\u{e611}",       // ""
+    "gruntfile.ls" => "\u{e611}",       // ""
+    "gshadow" => "\u{e615}",            // ""
+    "gulpfile.coffee" => "\u{e610}",    // ""
+    "gulpfile.js" => "\u{e610}",        // ""
+    "gulpfile.ls" => "\u{e610}",        // ""
+    "hostname" => "\u{e615}",           // ""
+    "hosts" => "\u{f502}",              // ""
+    ".htaccess" => "\u{e615}",          // ""
+    "htoprc" => "\u{e615}",             // ""
+    ".htpasswd" => "\u{e615}",          // ""
+    ".idlerc" => "\u{e235}",            // ""
+    "init.el" => "\u{e779}",            // ""
+    ".inputrc" => "\u{e615}",           // ""
+    "inputrc" => "\u{e615}",            // ""
+    "kbuild" => "\u{e615}",             // ""
+    "kconfig" => "\u{e615}",            // ""
+    "known_hosts" => "\u{e60a}",        // ""
+    ".kshrc" => "\u{f489}",             // ""
+    "license.md" => "\u{e60a}",         // ""
+    "license.txt" => "\u{e60a}",        // ""
+    "license" => "\u{e60a}",            // ""
+    "lsb-release" => "\u{e615}",        // ""
+    ".lynxrc" => "\u{e615}",            // ""
+    ".mailcap" => "\u{f6ef}",           // ""
+    "maintainers" => "\u{e60a}",        // ""
+    "makefile.ac" => "\u{e615}",        // ""
+    "makefile" => "\u{e615}",           // ""
+    "manifest" => "\u{f292}",           // ""
+    "metadata.xml" => "\u{f462}",       // ""
+    "mime.types" => "\u{fb44}",         // "פּ"
+    "module.symvers" => "\u{f471}",     // ""
+    "muttrc" => "\u{e615}",             // ""
+    "netlify.toml" => "\u{f233}",       // ""
+    ".node_repl_history" => "\u{e718}", // ""
+    "npmignore" => "\u{e71e}",          // ""
+    "os-release" => "\u{e615}",         // ""
+    "package.json" => "\u{e718}",       // ""
+    "package-lock.json" => "\u{e718}",  // ""
+    "packages.el" => "\u{e779}",        // ""
+    "passwd" => "\u{f023}",             // ""
+    "pkgbuild" => "\u{f303}",           // ""
+    "profile" => "\u{e615}",            // ""
+    ".profile" => "\u{f68c}",           // ""
+    ".python_history" => "\u{e606}",    // ""
+    "rc.lua" => "\u{e615}",             // ""
+    "readme" => "\u{e609}",             // ""
+    ".release.toml" => "\u{e7a8}",      // ""
+    "requirements.txt" => "\u{f81f}",   // ""
+    "robots.txt" => "\u{fba7}",         // "ﮧ"
+    "runtime.txt" => "\u{f81f}",        // ""
+    "shadow" => "\u{e615}",             // ""
+    ".shellcheckrc" => "\u{e615}",      // ""
+    "shells" => "\u{e615}",             // ""
+    ".sqlite_history" => "\u{e7c4}",    // ""
+    "sudoers" => "\u{f023}",            // ""
+    "sxhkdrc" => "\u{e615}",            // ""
+    "tigrc" => "\u{e615}",              // ""
+    "tox.ini" => "\u{f81f}",            // ""
+    "unlicense" => "\u{e60a}",          // ""
+    "url" => "\u{f0ac}",                // ""
+    "user-dirs.dirs" => "\u{e5fc}",     // ""
+    "vagrantfile" => "\u{e615}",        // ""
+    ".viminfo" => "\u{e62b}",           // ""
+    ".vimrc" => "\u{e62b}",             // ""
+    "vimrc" => "\u{e62b}",              // ""
+    "webpack.config.js" => "\u{fc29}",  // "ﰩ"
+    ".wgetrc" => "\u{e615}",            // ""
+    "wgetrc" => "\u{e615}",             // ""
+    ".xauthority" => "\u{e615}",        // ""
+    ".xinitrc" => "\u{e615}",           // ""
+    ".xmodmap" => "\u{e615}",           // ""
+    "xmonad.hs" => "\u{e615}",          // ""
+    ".xprofile" => "\u{e615}",          // ""
+    ".xresources" => "\u{e615}",        // ""
+    "zathurarc" => "\u{e615}",          // ""
+    ".zsh_history" => "\u{e615}",       // ""
+    ".zshrc" => "\u{f489}",             // ""
+};
+
+const ICONS_BY_DIR_NAME: phf::Map<&'static str, &'static str> = phf_map! {
+    "api" => "\u{f98c}",                // "歷"
+    "backups" => "\u{f56e}",            // ""
+    "bin" => "\u{e5fc}",                // ""
+    ".cargo" => "\u{e7a8}",             // ""
+    "conf.d" => "\u{e5fc}",             // ""
+    ".config" => "\u{e5fc}",            // ""
+    "config" => "\u{e5fc}",             // ""
+    "cron.daily" => "\u{e5fc}",         // ""
+    "cron.d" => "\u{e5fc}",             // ""
+    "cron.hourly" => "\u{e5fc}",        // ""
+    "cron.monthly" => "\u{e5fc}",       // ""
+    "cron.weekly" => "\u{e5fc}",        // ""
+    "css" => "\u{e749}",                // ""
+    ".dbus" => "\u{f013}",              // ""
+    "desktop" => "\u{f108}",            // ""
+    "doc" => "\u{f02d}",                // ""
+    "documents" => "\u{f02d}",          // ""
+    ".doom.d" => "\u{e779}",            // ""
+    "downloads" => "\u{f498}",          // ""
+    ".emacs.d" => "\u{e779}",           // ""
+    "etc" => "\u{e5fc}",                // ""
+    "favicons" => "\u{f005}",           // ""
+    ".github" => "\u{e5fd}",            // ""
+    ".git" => "\u{e5fb}",               // ""
+    ".gnupg" => "\u{f023}",             // ""
+    "gradle" => "\u{e70e}",             // ""
+    "hidden" => "\u{f023}",             // ""
+    "home" => "\u{f015}",               // ""
+    "img" => "\u{f1c5}",                // ""
+    "include" => "\u{e5fc}",            // ""
+    "js" => "\u{e74e}",                 // ""
+    ".jupyter" => "\u{e606}",           // ""
+    "lib64" => "\u{f121}",              // ""
+    "lib" => "\u{f121}",                // ""
+    "licenses" => "\u{e60a}",           // ""
+    "localized" => "\u{f179}",          // ""
+    "mail" => "\u{f6ef}",               // ""
+    "metadata" => "\u{e5fc}",           // ""
+    ".mozilla" => "\u{e786}",           // ""
+    "music" => "\u{f025}",              // ""
+    ".mutt" => "\u{e615}",              // ""
+    "node_modules" => "\u{e5fa}",       // ""
+    ".npm" => "\u{e5fa}",               // ""
+    "nvim" => "\u{e62b}",               // ""
+    "pictures" => "\u{f03e}",           // ""
+    ".pki" => "\u{f023}",               // ""
+    "portage" => "\u{e5fc}",            // ""
+    "public" => "\u{f415}",             // ""
+    "__pycache__" => "\u{f81f}",        // ""
+    "root" => "\u{f023}",               // ""
+    "rubydoc" => "\u{e73b}",            // ""
+    ".rustup" => "\u{e7a8}",            // ""
+    ".rvm" => "\u{e21e}",               // ""
+    "sass" => "\u{e603}",               // ""
+    "sbin" => "\u{e5fc}",               // ""
+    "scripts" => "\u{f489}",            // ""
+    "scss" => "\u{e603}",               // ""
+    "share" => "\u{f064}",              // ""
+    "src" => "\u{f121}",                // ""
+    ".ssh" => "\u{f023}",               // ""
+    "styles" => "\u{e749}",             // ""
+    ".trash" => "\u{f1f8}",             // ""
+    "ts" => "\u{e628}",                 // ""
+    "venv" => "\u{f81f}",               // ""
+    "videos" => "\u{f03d}",             // ""
+    ".vim" => "\u{e62b}",               // ""
+    "vim" => "\u{e62b}",                // ""
+    ".vscode" => "\u{e70c}",            // ""
+    "xbps.d" => "\u{e5fc}",             // ""
+    "xorg.conf.d" => "\u{e5fc}",        // ""
+};
+
+const ICONS_BY_EXTENSION: phf::Map<&'static str, &'static str> = phf_map! {
+    "1" => "\u{f02d}",               // ""
+    "2" => "\u{f02d}",               // ""
+    "3" => "\u{f02d}",               // ""
+    "4" => "\u{f02d}",               // ""
+    "5" => "\u{f02d}",               // ""
+    "6" => "\u{f02d}",               // ""
+    "7" => "\u{f02d}",               // ""
+    "7z" => "\u{f410}",              // ""
+    "8" => "\u{f02d}",               // ""
+    "ai" => "\u{e7b4}",              // ""
+    "ape" => "\u{f001}",             // ""
+    "apk" => "\u{e70e}",             // ""
+    "asc" => "\u{f023}",             // ""
+    "asm" => "\u{f471}",             // ""
+    "asp" => "\u{f121}",             // ""
+    "a" => "\u{e624}",               // ""
+    "avi" => "\u{f008}",             // ""
+    "avro" => "\u{e60b}",            // ""
+    "awk" => "\u{f489}",             // ""
+    "bak" => "\u{f56e}",             // ""
+    "bash_history" => "\u{f489}",    // ""
+    "bash_profile" => "\u{f489}",    // ""
+    "bashrc" => "\u{f489}",          // ""
+    "bash" => "\u{f489}",            // ""
+    "bat" => "\u{f17a}",             // ""
+    "bin" => "\u{f489}",             // ""
+    "bio" => "\u{f910}",             // "蘿"
+    "bmp" => "\u{f1c5}",             // ""
+    "bz2" => "\u{f410}",             // ""
+    "cc" => "\u{e61d}",              // ""
+    "cfg" => "\u{e615}",             // ""
+    "class" => "\u{e738}",           // ""
+    "cljs" => "\u{e76a}",            // ""
+    "clj" => "\u{e768}",             // ""
+    "cls" => "\u{e600}",             // ""
+    "cl" => "\u{f671}",              // ""
+    "coffee" => "\u{f0f4}",          // ""
+    "conf" => "\u{e615}",            // ""
+    "cpp" => "\u{e61d}",             // ""
+    "cp" => "\u{e61d}",              // ""
+    "cshtml" => "\u{f1fa}",          // ""
+    "csh" => "\u{f489}",             // ""
+    "csproj" => "\u{f81a}",          // ""
+    "css" => "\u{e749}",             // ""
+    "cs" => "\u{f81a}",              // ""
+    "csv" => "\u{f1c3}",             // ""
+    "csx" => "\u{f81a}",             // ""
+    "c++" => "\u{e61d}",             // ""
+    "c" => "\u{e61e}",               // ""
+    "cue" => "\u{f001}",             // ""
+    "cxx" => "\u{e61d}",             // ""
+    "d.ts" => "\u{e628}",            // ""
+    "dart" => "\u{e798}",            // ""
+    "dat" => "\u{f1c0}",             // ""
+    "db" => "\u{f1c0}",              // ""
+    "deb" => "\u{f187}",             // ""
+    "desktop" => "\u{f108}",         // ""
+    "diff" => "\u{e728}",            // ""
+    "dll" => "\u{f17a}",             // ""
+    "dockerfile" => "\u{f308}",      // ""
+    "doc" => "\u{f1c2}",             // ""
+    "docx" => "\u{f1c2}",            // ""
+    "ds_store" => "\u{f179}",        // ""
+    "dump" => "\u{f1c0}",            // ""
+    "ebook" => "\u{e28b}",           // ""
+    "ebuild" => "\u{f30d}",          // ""
+    "eclass" => "\u{f30d}",          // ""
+    "editorconfig" => "\u{e615}",    // ""
+    "ejs" => "\u{e618}",             // ""
+    "elc" => "\u{f671}",             // ""
+    "elf" => "\u{f489}",             // ""
+    "elm" => "\u{e62c}",             // ""
+    "el" => "\u{f671}",              // ""
+    "env" => "\u{f462}",             // ""
+    "eot" => "\u{f031}",             // ""
+    "epub" => "\u{e28a}",            // ""
+    "erb" => "\u{e73b}",             // ""
+    "erl" => "\u{e7b1}",             // ""
+    "exe" => "\u{f17a}",             // ""
+    "exs" => "\u{e62d}",             // ""
+    "ex" => "\u{e62d}",              // ""
+    "fish" => "\u{f489}",            // ""
+    "flac" => "\u{f001}",            // ""
+    "flv" => "\u{f008}",             // ""
+    "font" => "\u{f031}",            // ""
+    "fpl" => "\u{f910}",             // "蘿"
+    "fsi" => "\u{e7a7}",             // ""
+    "fs" => "\u{e7a7}",              // ""
+    "fsx" => "\u{e7a7}",             // ""
+    "gdoc" => "\u{f1c2}",            // ""
+    "gemfile" => "\u{e21e}",         // ""
+    "gemspec" => "\u{e21e}",         // ""
+    "gform" => "\u{f298}",           // ""
+    "gif" => "\u{f1c5}",             // ""
+    "git" => "\u{f1d3}",             // ""
+    "go" => "\u{e627}",              // ""
+    "gradle" => "\u{e70e}",          // ""
+    "gsheet" => "\u{f1c3}",          // ""
+    "gslides" => "\u{f1c4}",         // ""
+    "guardfile" => "\u{e21e}",       // ""
+    "gz" => "\u{f410}",              // ""
+    "hbs" => "\u{e60f}",             // ""
+    "heic" => "\u{f1c5}",            // ""
+    "heif" => "\u{f1c5}",            // ""
+    "heix" => "\u{f1c5}",            // ""
+    "hh" => "\u{f0fd}",              // ""
+    "hpp" => "\u{f0fd}",             // ""
+    "hs" => "\u{e777}",              // ""
+    "html" => "\u{f13b}",            // ""
+    "htm" => "\u{f13b}",             // ""
+    "h" => "\u{f0fd}",               // ""
+    "hxx" => "\u{f0fd}",             // ""
+    "ico" => "\u{f1c5}",             // ""
+    "image" => "\u{f1c5}",           // ""
+    "img" => "\u{f1c0}",             // ""
+    "iml" => "\u{e7b5}",             // ""
+    "info" => "\u{e795}",            // ""
+    "ini" => "\u{e615}",             // ""
+    "ipynb" => "\u{e606}",           // ""
+    "iso" => "\u{f1c0}",             // ""
+    "j2" => "\u{e000}",              // ""
+    "jar" => "\u{e738}",             // ""
+    "java" => "\u{e738}",            // ""
+    "jinja" => "\u{e000}",           // ""
+    "jl" => "\u{e624}",              // ""
+    "jpeg" => "\u{f1c5}",            // ""
+    "jpg" => "\u{f1c5}",             // ""
+    "jsonc" => "\u{e60b}",           // ""
+    "json" => "\u{e60b}",            // ""
+    "js" => "\u{e74e}",              // ""
+    "jsx" => "\u{e7ba}",             // ""
+    "key" => "\u{e60a}",             // ""
+    "ksh" => "\u{f489}",             // ""
+    "kt" => "\u{e634}",              // ""
+    "kts" => "\u{e634}",             // ""
+    "ldb" => "\u{f1c0}",             // ""
+    "ld" => "\u{e624}",              // ""
+    "less" => "\u{e758}",            // ""
+    "lhs" => "\u{e777}",             // ""
+    "license" => "\u{e60a}",         // ""
+    "lisp" => "\u{f671}",            // ""
+    "list" => "\u{f03a}",            // ""
+    "localized" => "\u{f179}",       // ""
+    "lock" => "\u{f023}",            // ""
+    "log" => "\u{f18d}",             // ""
+    "lss" => "\u{e749}",             // ""
+    "lua" => "\u{e620}",             // ""
+    "lz" => "\u{f410}",              // ""
+    "m3u8" => "\u{f910}",            // "蘿"
+    "m3u" => "\u{f910}",             // "蘿"
+    "m4a" => "\u{f001}",             // ""
+    "m4v" => "\u{f008}",             // ""
+    "magnet" => "\u{f076}",          // ""
+    "man" => "\u{f02d}",             // ""
+    "markdown" => "\u{e609}",        // ""
+    "md" => "\u{e609}",              // ""
+    "mjs" => "\u{e74e}",             // ""
+    "mkd" => "\u{e609}",             // ""
+    "mk" => "\u{f085}",              // ""
+    "mkv" => "\u{f008}",             // ""
+    "mobi" => "\u{e28b}",            // ""
+    "mov" => "\u{f008}",             // ""
+    "mp3" => "\u{f001}",             // ""
+    "mp4" => "\u{f008}",             // ""
+    "msi" => "\u{f17a}",             // ""
+    "mustache" => "\u{e60f}",        // ""
+    "nix" => "\u{f313}",             // ""
+    "npmignore" => "\u{e71e}",       // ""
+    "ogg" => "\u{f001}",             // ""
+    "ogv" => "\u{f008}",             // ""
+    "old" => "\u{f56e}",             // ""
+    "opus" => "\u{f001}",            // ""
+    "orig" => "\u{f56e}",            // ""
+    "otf" => "\u{f031}",             // ""
+    "o" => "\u{e624}",               // ""
+    "pdf" => "\u{f1c1}",             // ""
+    "pem" => "\u{f805}",             // ""
+    "phar" => "\u{e608}",            // ""
+    "php" => "\u{e608}",             // ""
+    "pkg" => "\u{f187}",             // ""
+    "plist" => "\u{f302}",           // ""
+    "pls" => "\u{f910}",             // "蘿"
+    "pl" => "\u{e769}",              // ""
+    "pm" => "\u{e769}",              // ""
+    "png" => "\u{f1c5}",             // ""
+    "ppt" => "\u{f1c4}",             // ""
+    "pptx" => "\u{f1c4}",            // ""
+    "procfile" => "\u{e21e}",        // ""
+    "properties" => "\u{e60b}",      // ""
+    "ps1" => "\u{f489}",             // ""
+    "psd" => "\u{e7b8}",             // ""
+    "pub" => "\u{e60a}",             // ""
+    "pxm" => "\u{f1c5}",             // ""
+    "pyc" => "\u{e606}",             // ""
+    "py" => "\u{e606}",              // ""
+    "rakefile" => "\u{e21e}",        // ""
+    "rar" => "\u{f410}",             // ""
+    "razor" => "\u{f1fa}",           // ""
+    "rb" => "\u{e21e}",              // ""
+    "rdata" => "\u{fcd2}",           // "ﳒ"
+    "rdb" => "\u{e76d}",             // ""
+    "rdoc" => "\u{e609}",            // ""
+    "rds" => "\u{fcd2}",             // "ﳒ"
+    "readme" => "\u{e609}",          // ""
+    "rlib" => "\u{e7a8}",            // ""
+    "rl" => "\u{f11c}",              // ""
+    "rmd" => "\u{e609}",             // ""
+    "rpm" => "\u{f187}",             // ""
+    "rproj" => "\u{fac5}",           // "鉶"
+    "rspec_parallel" => "\u{e21e}",  // ""
+    "rspec_status" => "\u{e21e}",    // ""
+    "rspec" => "\u{e21e}",           // ""
+    "rss" => "\u{f09e}",             // ""
+    "rs" => "\u{e7a8}",              // ""
+    "rtf" => "\u{f15c}",             // ""
+    "rubydoc" => "\u{e73b}",         // ""
+    "r" => "\u{fcd2}",               // "ﳒ"
+    "ru" => "\u{e21e}",              // ""
+    "sass" => "\u{e603}",            // ""
+    "scala" => "\u{e737}",           // ""
+    "scpt" => "\u{f302}",            // ""
+    "scss" => "\u{e603}",            // ""
+    "shell" => "\u{f489}",           // ""
+    "sh" => "\u{f489}",              // ""
+    "sig" => "\u{e60a}",             // ""
+    "slim" => "\u{e73b}",            // ""
+    "sln" => "\u{e70c}",             // ""
+    "so" => "\u{e624}",              // ""
+    "spec.js" => "\u{f0c3}",         // ""
+    "spec.ts" => "\u{f0c3}",         // ""
+    "sqlite3" => "\u{e7c4}",         // ""
+    "sql" => "\u{f1c0}",             // ""
+    "srt" => "\u{f02d}",             // ""
+    "styl" => "\u{e600}",            // ""
+    "stylus" => "\u{e600}",          // ""
+    "sublime-package" => "\u{e7aa}", // ""
+    "sublime-session" => "\u{e7aa}", // ""
+    "sub" => "\u{f02d}",             // ""
+    "s" => "\u{f471}",               // ""
+    "svg" => "\u{f1c5}",             // ""
+    "swift" => "\u{e755}",           // ""
+    "swp" => "\u{e62b}",             // ""
+    "sym" => "\u{e624}",             // ""
+    "tar" => "\u{f410}",             // ""
+    "tar.bz2" => "\u{f410}",         // ""
+    "tar.gz" => "\u{f410}",          // ""
+    "tar.xz" => "\u{f410}",          // ""
+    "tar.zst" => "\u{f410}",         // ""
+    "test.js" => "\u{f0c3}",         // ""
+    "test.ts" => "\u{f0c3}",         // ""
+    "tex" => "\u{e600}",             // ""
+    "tgz" => "\u{f410}",             // ""
+    "tiff" => "\u{f1c5}",            // ""
+    "toml" => "\u{e60b}",            // ""
+    "torrent" => "\u{f98c}",         // "歷"
+    "trash" => "\u{f1f8}",           // ""
+    "ts" => "\u{e628}",              // ""
+    "tsx" => "\u{e7ba}",             // ""
+    "ttc" => "\u{f031}",             // ""
+    "ttf" => "\u{f031}",             // ""
+    "t" => "\u{e769}",               // ""
+    "twig" => "\u{e61c}",            // ""
+    "txt" => "\u{f15c}",             // ""
+    "video" => "\u{f008}",           // ""
+    "vim" => "\u{e62b}",             // ""
+    "vlc" => "\u{f910}",             // "蘿"
+    "vue" => "\u{fd42}",             // "﵂"
+    "wav" => "\u{f001}",             // ""
+    "webm" => "\u{f008}",            // ""
+    "webp" => "\u{f1c5}",            // ""
+    "windows" => "\u{f17a}",         // ""
+    "wma" => "\u{f001}",             // ""
+    "wmv" => "\u{f008}",             // ""
+    "woff2" => "\u{f031}",           // ""
+    "woff" => "\u{f031}",            // ""
+    "wpl" => "\u{f910}",             // "蘿"
+    "xbps" => "\u{f187}",            // ""
+    "xcf" => "\u{f1c5}",             // ""
+    "xls" => "\u{f1c3}",             // ""
+    "xlsx" => "\u{f1c3}",            // ""
+    "xml" => "\u{f121}",             // ""
+    "xul" => "\u{f269}",             // ""
+    "xz" => "\u{f410}",              // ""
+    "yaml" => "\u{e60b}",            // ""
+    "yml" => "\u{e60b}",             // ""
+    "zip" => "\u{f410}",             // ""
+    "zshrc" => "\u{f489}",           // ""
+    "zsh-theme" => "\u{f489}",       // ""
+    "zsh" => "\u{f489}",             // ""
+    "zst" => "\u{f410}",             // ""
+};
+
+const ICONS_BY_GLOB: &[(&str, &str)] = &[
+    ("changelog.*", "\u{e609}"),           // ""
+    ("docker-compose.*.yaml", "\u{f308}"), // ""
+    ("docker-compose.*.yml", "\u{f308}"),  // ""
+    ("dockerfile.*", "\u{f308}"),          // ""
+    ("license-*", "\u{e60a}"),             // ""
+    ("license.*", "\u{e60a}"),             // ""
+    ("readme.*", "\u{e609}"),              // ""
+    ("requirements*.txt", "\u{e606}"),     // ""
+    (".env.*", "\u{f462}"),                // ""
+];
+
+const EMOJI_BY_NAME: phf::Map<&'static str, &'static str> = phf_map! {
+    "cmakelists.txt" => "🔨",
+    ".env" => "🔒",
+    ".gitattributes" => "🌿",
+    ".gitignore" => "🌿",
+    ".gitmodules" => "🌿",
+    "cargo.lock" => "🦀",
+    "cargo.toml" => "🦀",
+    "changelog" => "📰",
+    "copyright" => "📜",
+    "docker-compose.yml" => "🐳",
+    "dockerfile" => "🐳",
+    "license" => "📜",
+    "license.md" => "📜",
+    "license.txt" => "📜",
+    "makefile" => "🔨",
+    "package.json" => "📦",
+    "package-lock.json" => "📦",
+    "readme" => "📖",
+};
+
+const EMOJI_BY_DIR_NAME: phf::Map<&'static str, &'static str> = phf_map! {
+    ".git" => "🌿",
+    ".gnupg" => "🔐",
+    ".ssh" => "🔐",
+    "node_modules" => "📦",
+};
+
+const EMOJI_BY_EXTENSION: phf::Map<&'static str, &'static str> = phf_map! {
+    "7z" => "📦",
+    "asc" => "🔑",
+    "bash" => "🐚",
+    "bz2" => "📦",
+    "c" => "💻",
+    "cpp" => "💻",
+    "css" => "🎨",
+    "csv" => "📊",
+    "db" => "💾",
+    "deb" => "📦",
+    "doc" => "📘",
+    "docx" => "📘",
+    "fish" => "🐚",
+    "flac" => "🎵",
+    "gif" => "🎨",
+    "go" => "🐹",
+    "gz" => "📦",
+    "h" => "💻",
+    "hpp" => "💻",
+    "htm" => "🌐",
+    "html" => "🌐",
+    "iso" => "💿",
+    "jar" => "☕",
+    "java" => "☕",
+    "jpeg" => "🎨",
+    "jpg" => "🎨",
+    "js" => "📜",
+    "json" => "🔧",
+    "key" => "🔑",
+    "lock" => "🔒",
+    "log" => "📋",
+    "md" => "📝",
+    "mkv" => "🎬",
+    "mov" => "🎬",
+    "mp3" => "🎵",
+    "mp4" => "🎬",
+    "ogg" => "🎵",
+    "pdf" => "📕",
+    "pem" => "🔑",
+    "png" => "🎨",
+    "py" => "🐍",
+    "rar" => "📦",
+    "rb" => "💎",
+    "rpm" => "📦",
+    "rs" => "🦀",
+    "sh" => "🐚",
+    "sig" => "🔑",
+    "spec.js" => "🧪",
+    "spec.ts" => "🧪",
+    "sql" => "💾",
+    "sqlite3" => "💾",
+    "svg" => "🎨",
+    "tar" => "📦",
+    "tar.bz2" => "📦",
+    "tar.gz" => "📦",
+    "tar.xz" => "📦",
+    "tar.zst" => "📦",
+    "test.js" => "🧪",
+    "test.ts" => "🧪",
+    "tgz" => "📦",
+    "toml" => "🔧",
+    "ts" => "📜",
+    "txt" => "📝",
+    "wav" => "🎵",
+    "webm" => "🎬",
+    "webp" => "🎨",
+    "xls" => "📊",
+    "xlsx" => "📊",
+    "xz" => "📦",
+    "yaml" => "🔧",
+    "yml" => "🔧",
+    "zip" => "📦",
+    "zsh" => "🐚",
+    "zst" => "📦",
+};
+
+const EMOJI_BY_GLOB: &[(&str, &str)] = &[
+    ("changelog.*", "📰"),
+    ("docker-compose.*.yaml", "🐳"),
+    ("docker-compose.*.yml", "🐳"),
+    ("dockerfile.*", "🐳"),
+    ("license-*", "📜"),
+    ("license.*", "📜"),
+    ("readme.*", "📖"),
+    (".env.*", "🔒"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn all_types() -> Vec<FileType> {
+        vec![
+            FileType::File { exec: false },
+            FileType::File { exec: true },
+            FileType::Directory,
+            FileType::Symlink {
+                target: PathBuf::from("a"),
+                chain: Vec::new(),
+                looped: false,
+                to_dir: false,
+                valid: true,
+            },
+            FileType::Symlink {
+                target: PathBuf::from("a"),
+                chain: Vec::new(),
+                looped: false,
+                to_dir: true,
+                valid: true,
+            },
+            FileType::BlockDevice,
+            FileType::CharDevice,
+            FileType::Pipe,
+            FileType::Socket,
+            FileType::Special,
+        ]
+    }
+
+    #[test]
+    fn type_fallback_test() {
+        for set in [IconSet::Nerd, IconSet::Emoji, IconSet::Ascii] {
+            for ftype in all_types() {
+                let icon = set.icon("unknown.unknown", &ftype);
+                assert!(icon.is_some_and(|i| !i.is_empty()));
+            }
+        }
+
+        for ftype in all_types() {
+            assert_eq!(IconSet::None.icon("cargo.toml", &ftype), None);
+        }
+    }
+
+    #[test]
+    fn precedence_test() {
+        let file = FileType::File { exec: false };
+        assert_eq!(IconSet::Nerd.icon("cargo.toml", &file), Some("\u{e7a8}"));
+        assert_eq!(IconSet::Nerd.icon("foo.toml", &file), Some("\u{e60b}"));
+        assert_eq!(IconSet::Emoji.icon("main.rs", &file), Some("🦀"));
+        assert_eq!(IconSet::Ascii.icon("main.rs", &file), Some("-"));
+        assert_eq!(IconSet::Ascii.icon("bin", &FileType::Directory), Some("+"));
+    }
+
+    #[test]
+    fn tables_test() {
+        let file = FileType::File { exec: false };
+        let dir = FileType::Directory;
+        for (set, table) in [(IconSet::Nerd, &NERD), (IconSet::Emoji, &EMOJI)] {
+            let names = [table.by_name, table.by_dir_name, table.by_extension];
+            for key in names.iter().flat_map(|map| map.keys()) {
+                assert_eq!(*key, key.to_lowercase(), "{:?} is not lowercase", key);
+            }
+            for &(glob, _) in table.by_glob {
+                assert_eq!(glob, glob.to_lowercase(), "{:?} is not lowercase", glob);
+            }
+
+            for (name, &icon) in table.by_name.entries() {
+                assert_eq!(set.icon(name, &file), Some(icon), "{}", name);
+                assert_eq!(
+                    set.icon(&name.to_uppercase(), &file),
+                    Some(icon),
+                    "{}",
+                    name
+                );
+            }
+            for (name, &icon) in table.by_dir_name.entries() {
+                assert_eq!(set.icon(name, &dir), Some(icon), "{}", name);
+                assert_eq!(set.icon(&name.to_uppercase(), &dir), Some(icon), "{}", name);
+            }
+            for (extension, &icon) in table.by_extension.entries() {
+                let name = format!("x.{}", extension);
+                assert_eq!(set.icon(&name, &file), Some(icon), "{}", name);
+                assert_eq!(
+                    set.icon(&name.to_uppercase(), &file),
+                    Some(icon),
+                    "{}",
+                    name
+                );
+            }
+            for &(glob, icon) in table.by_glob {
+                let name = glob.replace('*', "x");
+                assert_eq!(set.icon(&name, &file), Some(icon), "{}", glob);
+                assert_eq!(
+                    set.icon(&name.to_uppercase(), &file),
+                    Some(icon),
+                    "{}",
+                    glob
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn directory_test() {
+        let file = FileType::File { exec: false };
+        let dir = FileType::Directory;
+        let link = |to_dir| FileType::Symlink {
+            target: PathBuf::from("a"),
+            chain: Vec::new(),
+            looped: false,
+            to_dir,
+            valid: true,
+        };
+        let nerd = |name, ftype: &FileType| IconSet::Nerd.icon(name, ftype);
+        assert_eq!(nerd("lib", &dir), Some("\u{f121}"));
+        assert_eq!(nerd("Lib", &link(true)), Some("\u{f121}"));
+        assert_eq!(nerd("lib", &file), Some("\u{f016}"));
+        assert_eq!(nerd("lib", &link(false)), Some("\u{f481}"));
+        assert_eq!(nerd("config", &dir), Some("\u{e5fc}"));
+        assert_eq!(nerd("config", &file), Some("\u{e615}"));
+
+        // Directories are not matched by extension or glob
+        assert_eq!(nerd("site.js", &dir), Some("\u{f115}"));
+        assert_eq!(nerd("readme.d", &dir), Some("\u{f115}"));
+        assert_eq!(IconSet::Emoji.icon(".git", &file), Some("📄"));
+        assert_eq!(IconSet::Emoji.icon(".GIT", &dir), Some("🌿"));
+    }
+
+    #[test]
+    fn matcher_test() {
+        let file = FileType::File { exec: false };
+        let nerd = |name| IconSet::Nerd.icon(name, &file);
+        assert_eq!(nerd("CMakeLists.txt"), Some("\u{e615}"));
+        assert_eq!(nerd("foo.test.ts"), Some("\u{f0c3}"));
+        assert_eq!(nerd("index.d.ts"), Some("\u{e628}"));
+        assert_eq!(nerd("src.tar.gz"), Some("\u{f410}"));
+        assert_eq!(nerd("Dockerfile.prod"), Some("\u{f308}"));
+        assert_eq!(nerd("docker-compose.override.yml"), Some("\u{f308}"));
+        assert_eq!(nerd("ci.yml"), Some("\u{e60b}"));
+        assert_eq!(nerd("README.md"), Some("\u{e609}"));
+        assert_eq!(IconSet::Emoji.icon("foo.spec.js", &file), Some("🧪"));
+
+        // User-defined icons come first
+        let mut icons = Icons::from(IconSet::Nerd);
+        icons.custom.insert_extension("ts", "T".to_owned());
+        assert_eq!(icons.icon("foo.test.ts", &file), Some("T"));
+        assert_eq!(icons.icon("foo.rs", &file), nerd("foo.rs"));
+        assert_eq!(Icons::from(IconSet::None).icon("foo.rs", &file), None);
+    }
+}
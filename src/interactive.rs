@@ -0,0 +1,521 @@
+use std::{
+    fs,
+    io::{self, Write},
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType},
+};
+
+use crate::{
+    error::Error,
+    file::{Appearance, File},
+    render::{FRONT, FRONT_LAST, PREFIX, PREFIX_LAST},
+    sort::Sorter,
+    Config,
+};
+
+const HELP: &str = "↑↓ move  ←→ enter space expand  / filter  . hidden  q pick  esc quit";
+
+/// Lets the user pick an entry below the roots of `config` in the terminal
+///
+/// The tree is drawn on stderr, so the returned path can be captured from stdout like in
+/// `cd $(mtree -i)`. It is `None` if the user left without picking anything.
+pub fn browse(config: Config) -> Result<Option<PathBuf>, Error> {
+    let mut browser = Browser::new(&config)?;
+    let mut stderr = io::stderr();
+    let _screen = Screen::enter(&mut stderr)?;
+
+    loop {
+        let (_, height) = terminal::size()?;
+        browser.draw(&mut stderr, height)?;
+        if let Event::Key(key) = event::read()? {
+            match browser.handle(key) {
+                Action::Continue => {}
+                Action::Pick(path) => return Ok(Some(path)),
+                Action::Cancel => return Ok(None),
+            }
+        }
+    }
+}
+
+/// The terminal in raw mode on the alternate screen, which is restored when dropped
+struct Screen;
+
+impl Screen {
+    fn enter(writer: &mut impl Write) -> io::Result<Screen> {
+        terminal::enable_raw_mode()?;
+        let screen = Screen;
+        execute!(
+            writer,
+            terminal::EnterAlternateScreen,
+            terminal::DisableLineWrap,
+            cursor::Hide
+        )?;
+        Ok(screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = execute!(
+            io::stderr(),
+            cursor::Show,
+            terminal::EnableLineWrap,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// What to do after a key press
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Continue,
+    Pick(PathBuf),
+    Cancel,
+}
+
+struct Item {
+    file: File,
+    parent: Option<usize>,
+    /// The entries of a directory, once it has been read
+    children: Option<Vec<usize>>,
+    /// Why a directory could not be read
+    error: Option<Error>,
+    expanded: bool,
+}
+
+/// A line of the tree on screen
+struct Row {
+    item: usize,
+    /// The connectors in front of the name
+    prefix: String,
+}
+
+/// The state of the browser, apart from the terminal
+struct Browser {
+    /// Every entry read so far, directories are read when first expanded
+    items: Vec<Item>,
+    roots: Vec<usize>,
+    sorter: Sorter,
+    appearance: Appearance,
+    /// For the selected row, whose highlight colors would cancel
+    selection: Appearance,
+    /// Whether entries starting with `.` are shown
+    hidden: bool,
+    /// Only entries matching this and the directories leading to them are shown
+    query: String,
+    /// Whether keys are typed into the query
+    filtering: bool,
+    rows: Vec<Row>,
+    selected: usize,
+    /// The first row on screen
+    scroll: usize,
+    /// How many rows fit on screen
+    page: usize,
+}
+
+impl Browser {
+    fn new(config: &Config) -> Result<Self, Error> {
+        let appearance = |colors| Appearance {
+            colors,
+            icons: config.icons(),
+            escape: config.escape,
+            literal: config.literal,
+            resolve: false,
+            full_path: false,
+        };
+
+        let mut browser = Browser {
+            items: Vec::new(),
+            roots: Vec::new(),
+            sorter: config.sorter(),
+            appearance: appearance(config.colors(config.color.enabled_on(&io::stderr()))),
+            selection: appearance(None),
+            hidden: config.all,
+            query: String::new(),
+            filtering: false,
+            rows: Vec::new(),
+            selected: 0,
+            scroll: 0,
+            page: 1,
+        };
+        for root in &config.directories {
+            let file = File::root(root).map_err(|e| Error::root(root, e))?;
+            let id = browser.push(file, None);
+            browser.roots.push(id);
+            browser.expand(id);
+        }
+        browser.refresh();
+        Ok(browser)
+    }
+
+    fn push(&mut self, file: File, parent: Option<usize>) -> usize {
+        self.items.push(Item {
+            file,
+            parent,
+            children: None,
+            error: None,
+            expanded: false,
+        });
+        self.items.len() - 1
+    }
+
+    fn children(&self, id: usize) -> &[usize] {
+        self.items[id].children.as_deref().unwrap_or_default()
+    }
+
+    /// Shows the entries of the directory `id`, reading them the first time
+    fn expand(&mut self, id: usize) {
+        if !self.items[id].file.is_dir() {
+            return;
+        }
+        if self.items[id].children.is_none() {
+            let children = match read(self.items[id].file.path(), &self.sorter) {
+                Ok(files) => files
+                    .into_iter()
+                    .map(|file| self.push(file, Some(id)))
+                    .collect(),
+                Err(e) => {
+                    self.items[id].error = Some(e);
+                    Vec::new()
+                }
+            };
+            self.items[id].children = Some(children);
+        }
+        self.items[id].expanded = true;
+    }
+
+    /// Whether the entry `id` is shown if its parent is expanded
+    fn is_shown(&self, id: usize) -> bool {
+        let item = &self.items[id];
+        if !self.hidden && item.file.name().as_bytes().starts_with(b".") {
+            return false;
+        }
+        self.query.is_empty()
+            || fuzzy_match(&self.query, &item.file.name_lossy())
+            || (item.expanded && self.children(id).iter().any(|&c| self.is_shown(c)))
+    }
+
+    /// Rebuilds the rows, keeping the selected entry or else its closest shown ancestor selected
+    fn refresh(&mut self) {
+        let mut selected = self.rows.get(self.selected).map(|row| row.item);
+
+        let mut rows = Vec::new();
+        for &root in &self.roots {
+            rows.push(Row {
+                item: root,
+                prefix: String::new(),
+            });
+            self.push_rows(root, &mut String::new(), &mut rows);
+        }
+        self.rows = rows;
+
+        self.selected = loop {
+            let Some(id) = selected else {
+                break 0;
+            };
+            match self.rows.iter().position(|row| row.item == id) {
+                Some(i) => break i,
+                None => selected = self.items[id].parent,
+            }
+        };
+    }
+
+    fn push_rows(&self, id: usize, indent: &mut String, rows: &mut Vec<Row>) {
+        if !self.items[id].expanded {
+            return;
+        }
+
+        let shown: Vec<_> = self
+            .children(id)
+            .iter()
+            .copied()
+            .filter(|&child| self.is_shown(child))
+            .collect();
+        for (i, &child) in shown.iter().enumerate() {
+            let last = i + 1 == shown.len();
+            rows.push(Row {
+                item: child,
+                prefix: format!("{}{} ", indent, if last { FRONT_LAST } else { FRONT }),
+            });
+
+            let len = indent.len();
+            indent.push_str(if last { PREFIX_LAST } else { PREFIX });
+            self.push_rows(child, indent, rows);
+            indent.truncate(len);
+        }
+    }
+
+    fn select(&mut self, row: usize) {
+        self.selected = row.min(self.rows.len() - 1);
+    }
+
+    fn handle(&mut self, key: KeyEvent) -> Action {
+        if key.kind == KeyEventKind::Release {
+            return Action::Continue;
+        }
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Action::Cancel;
+        }
+
+        if self.filtering {
+            match key.code {
+                KeyCode::Char(c) => self.query.push(c),
+                KeyCode::Backspace => {
+                    self.query.pop();
+                }
+                KeyCode::Enter => self.filtering = false,
+                KeyCode::Esc => {
+                    self.filtering = false;
+                    self.query.clear();
+                }
+                // Moving around works while typing
+                _ => return self.navigate(key.code),
+            }
+            self.refresh();
+            return Action::Continue;
+        }
+
+        match key.code {
+            KeyCode::Char('/') => self.filtering = true,
+            KeyCode::Char('.') => {
+                self.hidden = !self.hidden;
+                self.refresh();
+            }
+            KeyCode::Char('q') => {
+                let item = &self.items[self.rows[self.selected].item];
+                return Action::Pick(item.file.path().to_path_buf());
+            }
+            KeyCode::Esc => return Action::Cancel,
+            code => return self.navigate(code),
+        }
+        Action::Continue
+    }
+
+    /// Moves the selection or expands and collapses the selected directory
+    fn navigate(&mut self, code: KeyCode) -> Action {
+        let id = self.rows[self.selected].item;
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Down | KeyCode::Char('j') => self.select(self.selected + 1),
+            KeyCode::PageUp => self.select(self.selected.saturating_sub(self.page)),
+            KeyCode::PageDown => self.select(self.selected + self.page),
+            KeyCode::Home => self.select(0),
+            KeyCode::End => self.select(usize::MAX),
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.expand(id);
+                self.refresh();
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                if self.items[id].expanded {
+                    self.items[id].expanded = false;
+                    self.refresh();
+                } else if let Some(parent) = self.items[id].parent {
+                    if let Some(row) = self.rows.iter().position(|row| row.item == parent) {
+                        self.selected = row;
+                    }
+                }
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if self.items[id].expanded {
+                    self.items[id].expanded = false;
+                } else {
+                    self.expand(id);
+                }
+                self.refresh();
+            }
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    /// Draws the rows around the selection and a status line below them
+    fn draw(&mut self, writer: &mut impl Write, height: u16) -> io::Result<()> {
+        self.page = usize::from(height.saturating_sub(1)).max(1);
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + self.page {
+            self.scroll = self.selected + 1 - self.page;
+        }
+
+        let rows = self
+            .rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(self.page);
+        for (y, (i, row)) in rows.enumerate() {
+            let item = &self.items[row.item];
+            queue!(writer, cursor::MoveTo(0, y as u16), Print(&row.prefix))?;
+            if i == self.selected {
+                queue!(
+                    writer,
+                    SetAttribute(Attribute::Reverse),
+                    Print(item.file.styled(&self.selection)),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                queue!(writer, Print(item.file.styled(&self.appearance)))?;
+            }
+            if let Some(error) = &item.error {
+                queue!(writer, Print(format!("  [{}]", error)))?;
+            }
+            queue!(writer, terminal::Clear(ClearType::UntilNewLine))?;
+        }
+        let drawn = (self.rows.len() - self.scroll).min(self.page);
+        queue!(
+            writer,
+            cursor::MoveTo(0, drawn as u16),
+            terminal::Clear(ClearType::FromCursorDown)
+        )?;
+
+        let status = if self.filtering || !self.query.is_empty() {
+            format!("/{}", self.query)
+        } else {
+            HELP.to_owned()
+        };
+        queue!(
+            writer,
+            cursor::MoveTo(0, height.saturating_sub(1)),
+            SetAttribute(Attribute::Dim),
+            Print(status),
+            SetAttribute(Attribute::Reset)
+        )?;
+        writer.flush()
+    }
+}
+
+/// The entries of the directory at `path` which could be read, in display order
+fn read(path: &Path, sorter: &Sorter) -> Result<Vec<File>, Error> {
+    let mut files: Vec<_> = fs::read_dir(path)
+        .map_err(Error::read_dir)?
+        .filter_map(|entry| File::from_path(&entry.ok()?.path()).ok())
+        .collect();
+    sorter.sort(&mut files, |file| file);
+    Ok(files)
+}
+
+/// Whether the characters of `query` appear in `name` in order, ignoring case
+fn fuzzy_match(query: &str, name: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|q| name.any(|c| c == q))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icons::IconSet;
+    use pretty_assertions::assert_eq;
+
+    fn press(browser: &mut Browser, keys: &str) -> Action {
+        let mut action = Action::Continue;
+        for c in keys.chars() {
+            let code = match c {
+                '↑' => KeyCode::Up,
+                '↓' => KeyCode::Down,
+                '←' => KeyCode::Left,
+                '→' => KeyCode::Right,
+                '⏎' => KeyCode::Enter,
+                '⎋' => KeyCode::Esc,
+                '⌫' => KeyCode::Backspace,
+                c => KeyCode::Char(c),
+            };
+            action = browser.handle(KeyEvent::new(code, KeyModifiers::NONE));
+        }
+        action
+    }
+
+    /// The rows with the selected one marked
+    fn lines(browser: &Browser) -> Vec<String> {
+        browser
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let mark = if i == browser.selected { ">" } else { " " };
+                let name = browser.items[row.item].file.name_lossy();
+                format!("{}{}{}", mark, row.prefix, name)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fuzzy_match_test() {
+        assert!(fuzzy_match("", "anything"));
+        assert!(fuzzy_match("mrs", "main.rs"));
+        assert!(fuzzy_match("CTX", "c.txt"));
+        assert!(!fuzzy_match("rsm", "main.rs"));
+    }
+
+    #[test]
+    fn browse_test() {
+        let config = Config {
+            directories: vec![PathBuf::from("test")],
+            icons: IconSet::None,
+            ..Config::default()
+        };
+        let mut browser = Browser::new(&config).unwrap();
+        assert_eq!(
+            lines(&browser),
+            [">test", " ├── a", " ├── c.txt", " ├── d", " └── d.txt"]
+        );
+
+        // Directories are read when expanded
+        press(&mut browser, "↓↓↓⏎↓→↓");
+        assert_eq!(
+            lines(&browser),
+            [
+                " test",
+                " ├── a",
+                " ├── c.txt",
+                " ├── d",
+                " │   ├── e",
+                ">│   │   └── f",
+                " │   └── h.txt",
+                " └── d.txt"
+            ]
+        );
+
+        press(&mut browser, ".");
+        assert_eq!(
+            lines(&browser)[5..7],
+            [" │   │   ├── .gitkeep", ">│   │   └── f"]
+        );
+        press(&mut browser, ".←←←");
+        assert_eq!(
+            lines(&browser),
+            [
+                " test",
+                " ├── a",
+                " ├── c.txt",
+                ">├── d",
+                " │   ├── e",
+                " │   └── h.txt",
+                " └── d.txt"
+            ]
+        );
+
+        // Filtering keeps the directories leading to matches
+        press(&mut browser, "/htx");
+        assert_eq!(lines(&browser), [" test", ">└── d", "     └── h.txt"]);
+        press(&mut browser, "⏎↓");
+        assert_eq!(
+            press(&mut browser, "q"),
+            Action::Pick(PathBuf::from("test/d/h.txt"))
+        );
+        press(&mut browser, "/⎋");
+        assert_eq!(lines(&browser).len(), 7);
+        assert_eq!(press(&mut browser, "⎋"), Action::Cancel);
+    }
+}
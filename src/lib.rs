@@ -0,0 +1,32 @@
+//! Lists the contents of directories as a tree
+//!
+//! [`run`] is what the `mtree` binary does. To embed the walk elsewhere, [`TreeWalker`] yields the
+//! entries of a tree in display order and [`Formatter`] draws them like `mtree` does.
+
+pub mod color;
+mod config;
+pub mod details;
+pub mod error;
+mod escape;
+pub mod file;
+pub mod filter;
+pub mod git;
+mod gitignore;
+pub mod icons;
+mod interactive;
+pub mod matcher;
+pub mod render;
+mod settings;
+pub mod sort;
+pub mod stats;
+#[cfg(test)]
+mod testing;
+mod tree;
+mod walker;
+
+pub use config::Config;
+pub use error::Error;
+pub use interactive::browse;
+pub use render::TextRenderer as Formatter;
+pub use tree::run;
+pub use walker::{Entry, TreeWalker, Walk};
@@ -1,34 +1,45 @@
 use std::{
-    io,
-    path::PathBuf,
+    io::{self, Write},
+    os::unix::ffi::OsStrExt,
+    process::ExitCode,
 };
 
-use clap::Parser;
-
-mod file;
-mod tree;
-
-#[derive(Parser)]
-#[command(author, version, about)]
-pub struct Config {
-    #[arg(default_value = ".")]
-    directory: PathBuf,
-
-    /// Do not ignore entries starting with .
-    #[arg(short, long)]
-    all: bool,
-
-    /// Only display directories ignoring all files
-    #[arg(short, long)]
-    directory_only: bool,
-
-    /// The recursion depth
-    #[arg(short, long, default_value_t = 5)]
-    limit: u32,
-}
-
-fn main() -> io::Result<()> {
-    let config = Config::parse();
-
-    tree::tree(config)
+use mtree::{Config, Error};
+
+fn main() -> ExitCode {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("mtree: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let strict = config.strict();
+
+    if config.interactive() {
+        return match mtree::browse(config) {
+            Ok(Some(path)) => {
+                let mut stdout = io::stdout();
+                let _ = stdout.write_all(path.as_os_str().as_bytes());
+                let _ = stdout.write_all(b"\n");
+                ExitCode::SUCCESS
+            }
+            Ok(None) => ExitCode::FAILURE,
+            Err(e) => {
+                eprintln!("mtree: {}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    match mtree::run(config) {
+        Ok(stats) if strict && stats.errors > 0 => ExitCode::FAILURE,
+        Ok(_) => ExitCode::SUCCESS,
+        // The reader went away early, e.g. `mtree | head`
+        Err(Error::Output(e)) if e.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("mtree: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
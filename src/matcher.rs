@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use globset::{GlobBuilder, GlobMatcher};
+
+/// Finds the value for the file name `name`, trying in order
+///
+/// 1. the name, like `makefile` for `Makefile`
+/// 2. compound extensions from the longest, like `test.ts` for `foo.test.ts`
+/// 3. globs, like `docker-compose.*.yml`
+/// 4. the last extension, like `ts`
+///
+/// Case does not matter: names are tried as they are and then in lowercase, extensions are
+/// lowercase and globs should ignore case. Keys of names and extensions must be lowercase.
+/// A leading dot does not start an extension, so `.bashrc` has none and `.eslintrc.json` has `json`.
+pub fn find<T>(
+    name: &str,
+    by_name: impl Fn(&str) -> Option<T>,
+    by_extension: impl Fn(&str) -> Option<T>,
+    by_glob: impl FnOnce() -> Option<T>,
+) -> Option<T> {
+    let lowercase = name.to_lowercase();
+    let mut extensions = extensions(&lowercase);
+    let last = extensions.pop();
+
+    find_name(name, by_name)
+        .or_else(|| extensions.into_iter().find_map(&by_extension))
+        .or_else(by_glob)
+        .or_else(|| last.and_then(by_extension))
+}
+
+/// Finds the value for `name` as it is or else in lowercase, the first step of [`find`]
+pub fn find_name<T>(name: &str, by_name: impl Fn(&str) -> Option<T>) -> Option<T> {
+    by_name(name).or_else(|| {
+        let lowercase = name.to_lowercase();
+        (lowercase != name).then(|| by_name(&lowercase)).flatten()
+    })
+}
+
+/// The extensions of `name` from the longest, `a.tar.gz` has `tar.gz` and `gz`
+fn extensions(name: &str) -> Vec<&str> {
+    let start = usize::from(name.starts_with('.'));
+    name[start..]
+        .match_indices('.')
+        .map(|(i, _)| &name[start + i + 1..])
+        .filter(|extension| !extension.is_empty())
+        .collect()
+}
+
+/// Values for file names, looked up in the order of [`find`] regardless of case
+#[derive(Debug, Clone)]
+pub struct Matcher<T> {
+    names: HashMap<String, T>,
+    extensions: HashMap<String, T>,
+    /// Tried in the order they were added
+    globs: Vec<(GlobMatcher, T)>,
+}
+
+impl<T> Default for Matcher<T> {
+    fn default() -> Self {
+        Self {
+            names: HashMap::new(),
+            extensions: HashMap::new(),
+            globs: Vec::new(),
+        }
+    }
+}
+
+impl<T> Matcher<T> {
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty() && self.extensions.is_empty() && self.globs.is_empty()
+    }
+
+    pub fn insert_name(&mut self, name: &str, value: T) {
+        self.names.insert(name.to_lowercase(), value);
+    }
+
+    /// Adds a value for names ending in `.extension`
+    pub fn insert_extension(&mut self, extension: &str, value: T) {
+        self.extensions.insert(extension.to_lowercase(), value);
+    }
+
+    /// Adds a value for names matching `glob`, like `*.test.ts`
+    pub fn insert_glob(&mut self, glob: &str, value: T) -> Result<(), globset::Error> {
+        self.globs.push((glob_matcher(glob)?, value));
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&T> {
+        find(
+            name,
+            |name| self.names.get(name),
+            |extension| self.extensions.get(extension),
+            || {
+                self.globs
+                    .iter()
+                    .find(|(glob, _)| glob.is_match(name))
+                    .map(|(_, value)| value)
+            },
+        )
+    }
+}
+
+/// Compiles `glob` to match regardless of case
+pub(crate) fn glob_matcher(glob: &str) -> Result<GlobMatcher, globset::Error> {
+    let glob = GlobBuilder::new(glob).case_insensitive(true).build()?;
+    Ok(glob.compile_matcher())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn get_test() {
+        let mut matcher = Matcher::default();
+        matcher.insert_name("Cargo.toml", "name");
+        matcher.insert_extension("toml", "extension");
+        matcher.insert_glob("*.txt", "glob").unwrap();
+
+        assert_eq!(matcher.get("Cargo.toml"), Some(&"name"));
+        assert_eq!(matcher.get("rustfmt.toml"), Some(&"extension"));
+        assert_eq!(matcher.get("notes.txt"), Some(&"glob"));
+        assert_eq!(matcher.get(".toml"), None);
+        assert_eq!(matcher.get("README"), None);
+        assert_eq!(matcher.get("CARGO.TOML"), Some(&"name"));
+        assert_eq!(matcher.get("RUSTFMT.TOML"), Some(&"extension"));
+        assert_eq!(matcher.get("NOTES.TXT"), Some(&"glob"));
+        assert!(matcher.insert_glob("[", "broken").is_err());
+        assert!(Matcher::<()>::default().is_empty());
+    }
+
+    #[test]
+    fn extensions_test() {
+        assert_eq!(extensions("a.tar.gz"), ["tar.gz", "gz"]);
+        assert_eq!(extensions(".eslintrc.json"), ["json"]);
+        assert_eq!(extensions(".bashrc"), [""; 0]);
+        assert_eq!(extensions("a."), [""; 0]);
+        assert_eq!(extensions("Makefile"), [""; 0]);
+    }
+
+    #[test]
+    fn find_test() {
+        let mut matcher = Matcher::default();
+        matcher.insert_name("makefile", "makefile");
+        matcher.insert_extension("test.ts", "test");
+        matcher.insert_extension("ts", "ts");
+        matcher.insert_extension("yml", "yml");
+        matcher
+            .insert_glob("docker-compose.*.yml", "compose")
+            .unwrap();
+
+        assert_eq!(matcher.get("Makefile"), Some(&"makefile"));
+        assert_eq!(matcher.get("foo.test.ts"), Some(&"test"));
+        assert_eq!(matcher.get("foo.ts"), Some(&"ts"));
+        assert_eq!(matcher.get("docker-compose.override.yml"), Some(&"compose"));
+        assert_eq!(matcher.get("ci.yml"), Some(&"yml"));
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    io::{self, Write},
+    os::unix::ffi::OsStrExt,
+    path::PathBuf,
+};
+
+use super::{escape_markup, kind, Elided, Position, Renderer};
+use crate::{
+    details::Details,
+    error::Error,
+    file::{File, FileType},
+    icons::Icons,
+    stats::Stats,
+};
+
+const STYLE: &str = "ul.tree, ul.tree ul { list-style: none; padding-left: 1.5em; }
+ul.tree summary { cursor: pointer; }
+ul.tree a { text-decoration: none; }
+li.error { color: firebrick; }
+li.broken > a { text-decoration: line-through; }
+p.report { color: gray; }";
+
+/// A standalone page with directories as collapsible `<details>`
+pub struct HtmlRenderer {
+    icons: Icons,
+    details: Details,
+    base_href: Option<String>,
+    roots: Vec<PathBuf>,
+}
+
+impl HtmlRenderer {
+    pub fn new(
+        icons: Icons,
+        details: Details,
+        base_href: Option<String>,
+        roots: Vec<PathBuf>,
+    ) -> Self {
+        Self {
+            icons,
+            details,
+            base_href,
+            roots,
+        }
+    }
+
+    /// The link to `file`, relative to the base href if there is one
+    ///
+    /// Links are relative to the root if there is only one, so the page can be
+    /// served from it, and keep the root otherwise.
+    fn href(&self, file: &File) -> String {
+        let relative = match self.roots.as_slice() {
+            [root] => file.path().strip_prefix(root).unwrap_or(file.path()),
+            _ => file.path(),
+        };
+        let mut href = match &self.base_href {
+            Some(base) => format!("{}/", base.trim_end_matches('/')),
+            None => String::new(),
+        };
+        href.push_str(&percent_encode(relative.as_os_str().as_bytes()));
+
+        if matches!(file.ftype(), FileType::Directory) {
+            if href.is_empty() {
+                href.push('.');
+            }
+            if !href.ends_with('/') {
+                href.push('/');
+            }
+        }
+        escape_markup(&href)
+    }
+
+    fn label(&self, writer: &mut dyn Write, file: &File) -> io::Result<()> {
+        // The columns may hold names and a time format from the user
+        let mut columns = Vec::new();
+        self.details.write(&mut columns, file)?;
+        write!(
+            writer,
+            "{}",
+            escape_markup(&String::from_utf8_lossy(&columns))
+        )?;
+        if let Some(icon) = file.icon(&self.icons) {
+            write!(writer, "{} ", icon)?;
+        }
+        write!(
+            writer,
+            r#"<a href="{}">{}</a>"#,
+            self.href(file),
+            escape_markup(&file.name_lossy())
+        )?;
+        if let FileType::Symlink { target, .. } = file.ftype() {
+            write!(writer, " ⇒ {}", escape_markup(&target.to_string_lossy()))?;
+        }
+        Ok(())
+    }
+}
+
+fn indent(writer: &mut dyn Write, depth: u32) -> io::Result<()> {
+    write!(writer, "{:width$}", "", width = 2 * depth as usize)
+}
+
+impl Renderer for HtmlRenderer {
+    fn begin(&self, writer: &mut dyn Write) -> io::Result<()> {
+        writeln!(writer, "<!DOCTYPE html>")?;
+        writeln!(writer, "<html>")?;
+        writeln!(writer, "<head>")?;
+        writeln!(writer, r#"<meta charset="utf-8">"#)?;
+        writeln!(writer, r#"<meta name="generator" content="mtree">"#)?;
+        let title = self
+            .roots
+            .iter()
+            .map(|root| root.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(writer, "<title>{}</title>", escape_markup(&title))?;
+        writeln!(writer, "<style>\n{}\n</style>", STYLE)?;
+        writeln!(writer, "</head>")?;
+        writeln!(writer, "<body>")?;
+        writeln!(writer, r#"<ul class="tree">"#)
+    }
+
+    fn entry(&self, writer: &mut dyn Write, file: &File, pos: &Position) -> io::Result<()> {
+        indent(writer, pos.depth)?;
+        let broken = matches!(file.ftype(), FileType::Symlink { valid: false, .. });
+        write!(
+            writer,
+            r#"<li class="{}{}">"#,
+            kind(file),
+            if broken { " broken" } else { "" }
+        )?;
+
+        if pos.descend {
+            write!(writer, "<details open><summary>")?;
+            self.label(writer, file)?;
+            writeln!(writer, "</summary>")?;
+            indent(writer, pos.depth)?;
+            writeln!(writer, "<ul>")
+        } else {
+            self.label(writer, file)?;
+            writeln!(writer, "</li>")
+        }
+    }
+
+    fn leave(&self, writer: &mut dyn Write, _file: &File, pos: &Position) -> io::Result<()> {
+        indent(writer, pos.depth)?;
+        writeln!(writer, "</ul></details></li>")
+    }
+
+    fn elided(&self, writer: &mut dyn Write, elided: &Elided, pos: &Position) -> io::Result<()> {
+        indent(writer, pos.depth)?;
+        writeln!(writer, r#"<li class="elided">{}</li>"#, elided)
+    }
+
+    fn error(&self, writer: &mut dyn Write, error: &Error, pos: &Position) -> io::Result<()> {
+        indent(writer, pos.depth)?;
+        writeln!(
+            writer,
+            r#"<li class="error">[{}]</li>"#,
+            escape_markup(&error.to_string())
+        )
+    }
+
+    fn summary(&self, writer: &mut dyn Write, stats: &Stats) -> io::Result<()> {
+        writeln!(writer, "</ul>")?;
+        writeln!(writer, r#"<p class="report">{}</p>"#, stats)?;
+        writeln!(writer, "</body>")?;
+        writeln!(writer, "</html>")
+    }
+}
+
+/// Percent-encodes everything but unreserved characters and `/`, byte by byte so that names
+/// which are not valid UTF-8 keep linking to the file
+fn percent_encode(path: &[u8]) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for &b in path {
+        if b.is_ascii_alphanumeric() || b"-._~/".contains(&b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", b));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{details::TimeFormat, icons::IconSet};
+    use pretty_assertions::assert_eq;
+    use std::{ffi::OsStr, path::Path};
+
+    #[test]
+    fn percent_encode_test() {
+        assert_eq!(percent_encode(b"a b/c&d.txt"), "a%20b/c%26d.txt");
+        assert_eq!(percent_encode("ä".as_bytes()), "%C3%A4");
+    }
+
+    #[test]
+    fn non_utf8_href_test() {
+        let name = OsStr::from_bytes(b"dir/caf\xe9");
+        assert_eq!(percent_encode(name.as_bytes()), "dir/caf%E9");
+    }
+
+    #[test]
+    fn label_test() {
+        let details = Details {
+            time: Some(TimeFormat::Custom("<b>&</b>".into())),
+            ..Details::default()
+        };
+        let renderer = HtmlRenderer::new(IconSet::None.into(), details, None, vec![".".into()]);
+        let file = File::from_path(Path::new("Cargo.toml")).unwrap();
+        let mut output = Vec::new();
+        renderer.label(&mut output, &file).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            r#"[&lt;b&gt;&amp;&lt;/b&gt;]  <a href="Cargo.toml">Cargo.toml</a>"#
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    ffi::OsStr,
+    io::{self, Write},
+    os::unix::ffi::OsStrExt,
+};
+
+use super::{kind, Elided, Position, Renderer};
+use crate::{
+    error::Error,
+    file::{File, FileType},
+    stats::Stats,
+};
+
+/// Mirrors the layout of `tree -J`: one node per line, children nested in "contents"
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn begin(&self, writer: &mut dyn Write) -> io::Result<()> {
+        writeln!(writer, "[")
+    }
+
+    fn entry(&self, writer: &mut dyn Write, file: &File, pos: &Position) -> io::Result<()> {
+        node(writer, file, pos.depth, pos.descend, pos.is_last)
+    }
+
+    fn leave(&self, writer: &mut dyn Write, _file: &File, pos: &Position) -> io::Result<()> {
+        indent(writer, pos.depth)?;
+        end(writer, "]}", pos.is_last)
+    }
+
+    fn elided(&self, writer: &mut dyn Write, elided: &Elided, pos: &Position) -> io::Result<()> {
+        indent(writer, pos.depth)?;
+        write!(
+            writer,
+            "{{\"type\":\"elided\",\"directories\":{},\"files\":{}",
+            elided.directories, elided.files
+        )?;
+        end(writer, "}", pos.is_last)
+    }
+
+    fn error(&self, writer: &mut dyn Write, error: &Error, pos: &Position) -> io::Result<()> {
+        indent(writer, pos.depth)?;
+        write!(writer, "{{\"error\":")?;
+        string(writer, OsStr::new(&error.to_string()))?;
+        end(writer, "}", pos.is_last)
+    }
+
+    fn summary(&self, writer: &mut dyn Write, stats: &Stats) -> io::Result<()> {
+        report(writer, stats)
+    }
+}
+
+fn indent(writer: &mut dyn Write, depth: u32) -> io::Result<()> {
+    write!(writer, "{:width$}", "", width = 2 * (depth as usize + 1))
+}
+
+/// Writes `file`, leaving its "contents" open if `has_contents`
+fn node(
+    writer: &mut dyn Write,
+    file: &File,
+    depth: u32,
+    has_contents: bool,
+    is_last: bool,
+) -> io::Result<()> {
+    indent(writer, depth)?;
+
+    write!(writer, "{{\"type\":\"{}\",\"name\":", kind(file))?;
+    string(writer, file.name())?;
+    write!(writer, ",\"path\":")?;
+    string(writer, file.path().as_os_str())?;
+
+    match file.ftype() {
+        FileType::File { exec } => write!(writer, ",\"exec\":{}", exec)?,
+        FileType::Symlink {
+            target,
+            to_dir,
+            valid,
+            ..
+        } => {
+            write!(writer, ",\"target\":")?;
+            string(writer, target.as_os_str())?;
+            write!(writer, ",\"valid\":{},\"to_dir\":{}", valid, to_dir)?;
+        }
+        _ => {}
+    }
+    write!(writer, ",\"size\":{}", file.size())?;
+
+    if has_contents {
+        writeln!(writer, ",\"contents\":[")
+    } else {
+        end(writer, "}", is_last)
+    }
+}
+
+fn end(writer: &mut dyn Write, closing: &str, is_last: bool) -> io::Result<()> {
+    writeln!(writer, "{}{}", closing, if is_last { "" } else { "," })
+}
+
+fn report(writer: &mut dyn Write, stats: &Stats) -> io::Result<()> {
+    writeln!(writer, ",")?;
+    indent(writer, 0)?;
+    writeln!(
+        writer,
+        "{{\"type\":\"report\",\"directories\":{},\"files\":{},\"executables\":{},\
+        \"symlinks\":{},\"broken_symlinks\":{},\"block_devices\":{},\"char_devices\":{},\
+        \"pipes\":{},\"sockets\":{},\"special\":{},\"bytes\":{},\"skipped\":{},\"errors\":{}}}",
+        stats.directories,
+        stats.non_directories(),
+        stats.executables,
+        stats.symlinks,
+        stats.broken_symlinks,
+        stats.block_devices,
+        stats.char_devices,
+        stats.pipes,
+        stats.sockets,
+        stats.special,
+        stats.bytes,
+        stats.skipped,
+        stats.errors,
+    )?;
+    writeln!(writer, "]")
+}
+
+/// Writes `s` as a quoted JSON string
+///
+/// Bytes which are not valid UTF-8 become the lone surrogates `\udc80` to `\udcff`, like
+/// Python's `surrogateescape`, so the original name can be restored by decoders aware of it.
+fn string(writer: &mut dyn Write, s: &OsStr) -> io::Result<()> {
+    write!(writer, "\"")?;
+    for chunk in s.as_bytes().utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '"' => write!(writer, "\\\"")?,
+                '\\' => write!(writer, "\\\\")?,
+                '\n' => write!(writer, "\\n")?,
+                '\r' => write!(writer, "\\r")?,
+                '\t' => write!(writer, "\\t")?,
+                c if c.is_control() => write!(writer, "\\u{:04x}", c as u32)?,
+                c => write!(writer, "{}", c)?,
+            }
+        }
+        for &b in chunk.invalid() {
+            write!(writer, "\\u{:04x}", 0xdc00 | b as u32)?;
+        }
+    }
+    write!(writer, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn string_test() {
+        let mut output = Vec::new();
+        string(&mut output, OsStr::new("a \"b\"\\\n\u{1}ä")).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            r#""a \"b\"\\\n\u0001ä""#
+        );
+
+        let mut output = Vec::new();
+        string(&mut output, OsStr::from_bytes(b"caf\xe9.txt")).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), r#""caf\udce9.txt""#);
+    }
+}
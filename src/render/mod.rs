@@ -0,0 +1,177 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+use clap::ValueEnum;
+
+use crate::{
+    error::Error,
+    file::{File, FileType},
+    stats::{plural, Stats},
+};
+
+mod html;
+mod json;
+mod text;
+mod xml;
+
+pub use html::HtmlRenderer;
+pub use json::JsonRenderer;
+pub use text::TextRenderer;
+pub(crate) use text::{FRONT, FRONT_LAST, PREFIX, PREFIX_LAST};
+pub use xml::XmlRenderer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    /// Indented tree with box-drawing characters
+    Text,
+    /// Nested JSON document like `tree -J`
+    Json,
+    /// XML document like `tree -X`
+    Xml,
+    /// Standalone HTML page with a collapsible list
+    Html,
+}
+
+/// Where an entry is located within the tree
+pub struct Position<'a> {
+    /// The root has depth 0
+    pub depth: u32,
+    /// Whether this is the last entry of its directory
+    pub is_last: bool,
+    /// Whether each ancestor below the root was the last entry of its directory
+    pub ancestors: &'a [bool],
+    /// Whether the children of this entry follow before [`Renderer::leave`]
+    pub descend: bool,
+}
+
+/// The entries of a directory left out by `--max-entries`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Elided {
+    pub directories: u64,
+    pub files: u64,
+    /// Their total size, so directory totals with `--du` stay accurate
+    pub size: u64,
+}
+
+impl Elided {
+    pub fn new(files: impl Iterator<Item = File>) -> Self {
+        let mut elided = Elided::default();
+        for file in files {
+            if file.is_dir() {
+                elided.directories += 1;
+            } else {
+                elided.files += 1;
+            }
+            elided.size += file.size();
+        }
+        elided
+    }
+
+    pub fn entries(&self) -> u64 {
+        self.directories + self.files
+    }
+}
+
+impl Display for Elided {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "… {} more {} ({}, {})",
+            self.entries(),
+            if self.entries() == 1 {
+                "entry"
+            } else {
+                "entries"
+            },
+            plural(self.directories, "dir", "dirs"),
+            plural(self.files, "file", "files")
+        )
+    }
+}
+
+/// Turns the events of a traversal into output
+pub trait Renderer {
+    fn begin(&self, _writer: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn entry(&self, writer: &mut dyn Write, file: &File, pos: &Position) -> io::Result<()>;
+
+    /// Called after all children of an entry which was descended into
+    fn leave(&self, _writer: &mut dyn Write, _file: &File, _pos: &Position) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Called after the children of a directory if some were left out
+    fn elided(&self, writer: &mut dyn Write, elided: &Elided, pos: &Position) -> io::Result<()>;
+
+    /// Called for a problem reading the children of the entry above, after its children
+    fn error(&self, writer: &mut dyn Write, error: &Error, pos: &Position) -> io::Result<()>;
+
+    fn summary(&self, writer: &mut dyn Write, stats: &Stats) -> io::Result<()>;
+}
+
+/// The node type names used by `tree -J` and `tree -X`
+fn kind(file: &File) -> &'static str {
+    match file.ftype() {
+        FileType::File { .. } => "file",
+        FileType::Directory => "directory",
+        FileType::Symlink { .. } => "link",
+        FileType::BlockDevice => "block",
+        FileType::CharDevice => "char",
+        FileType::Pipe => "fifo",
+        FileType::Socket => "socket",
+        FileType::Special => "unknown",
+    }
+}
+
+/// Escapes the characters with a special meaning in XML and HTML
+fn escape_markup(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn elided_test() {
+        let elided = Elided {
+            directories: 56,
+            files: 1178,
+            size: 0,
+        };
+        assert_eq!(
+            elided.to_string(),
+            "… 1234 more entries (56 dirs, 1178 files)"
+        );
+
+        let one = Elided {
+            files: 1,
+            ..Elided::default()
+        };
+        assert_eq!(one.to_string(), "… 1 more entry (0 dirs, 1 file)");
+    }
+
+    #[test]
+    fn escape_markup_test() {
+        assert_eq!(
+            escape_markup(r#"<a href="x">'&'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::io::{self, Write};
+
+use super::{Elided, Position, Renderer};
+use crate::{
+    details::Details,
+    error::Error,
+    file::{Appearance, File},
+    stats::Stats,
+    Entry,
+};
+
+pub(crate) const PREFIX: &str = "│   ";
+pub(crate) const PREFIX_LAST: &str = "    ";
+pub(crate) const FRONT: &str = "├──";
+pub(crate) const FRONT_LAST: &str = "└──";
+
+pub struct TextRenderer {
+    appearance: Appearance,
+    details: Details,
+    /// Whether entries are indented with connectors, otherwise the output is a flat list
+    indent: bool,
+}
+
+impl TextRenderer {
+    pub fn new(appearance: Appearance, details: Details, indent: bool) -> Self {
+        Self {
+            appearance,
+            details,
+            indent,
+        }
+    }
+
+    /// Writes the line for an entry of a [`TreeWalker`](crate::TreeWalker)
+    pub fn write(&self, writer: &mut dyn Write, entry: &Entry) -> io::Result<()> {
+        let pos = Position {
+            depth: entry.depth,
+            is_last: entry.is_last,
+            ancestors: &entry.ancestors,
+            descend: false,
+        };
+        match &entry.file {
+            Ok(file) => self.entry(writer, file, &pos),
+            Err(error) => self.error(writer, error, &pos),
+        }
+    }
+}
+
+fn connectors(writer: &mut dyn Write, pos: &Position) -> io::Result<()> {
+    if pos.depth > 0 {
+        for &last in pos.ancestors {
+            write!(writer, "{}", if last { PREFIX_LAST } else { PREFIX })?;
+        }
+        write!(writer, "{} ", if pos.is_last { FRONT_LAST } else { FRONT })?;
+    }
+    Ok(())
+}
+
+impl Renderer for TextRenderer {
+    fn entry(&self, writer: &mut dyn Write, file: &File, pos: &Position) -> io::Result<()> {
+        if self.indent {
+            connectors(writer, pos)?;
+        }
+        self.details.write(writer, file)?;
+        writeln!(writer, "{}", file.styled(&self.appearance))
+    }
+
+    fn elided(&self, writer: &mut dyn Write, elided: &Elided, pos: &Position) -> io::Result<()> {
+        if self.indent {
+            connectors(writer, pos)?;
+        }
+        writeln!(writer, "{}", elided)
+    }
+
+    fn error(&self, writer: &mut dyn Write, error: &Error, pos: &Position) -> io::Result<()> {
+        if self.indent {
+            connectors(writer, pos)?;
+        }
+        writeln!(writer, "[{}]", error)
+    }
+
+    fn summary(&self, writer: &mut dyn Write, stats: &Stats) -> io::Result<()> {
+        writeln!(writer, "{}", stats)
+    }
+}
@@ -0,0 +1,114 @@
+use std::io::{self, Write};
+
+use super::{escape_markup, kind, Elided, Position, Renderer};
+use crate::{
+    details::{self, Details},
+    error::Error,
+    file::{File, FileType},
+    stats::Stats,
+};
+
+/// Follows the schema of `tree -X`
+pub struct XmlRenderer {
+    details: Details,
+}
+
+impl XmlRenderer {
+    pub fn new(details: Details) -> Self {
+        Self { details }
+    }
+}
+
+fn indent(writer: &mut dyn Write, depth: u32) -> io::Result<()> {
+    write!(writer, "{:width$}", "", width = 2 * (depth as usize + 1))
+}
+
+impl Renderer for XmlRenderer {
+    fn begin(&self, writer: &mut dyn Write) -> io::Result<()> {
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(writer, "<tree>")
+    }
+
+    fn entry(&self, writer: &mut dyn Write, file: &File, pos: &Position) -> io::Result<()> {
+        indent(writer, pos.depth)?;
+        write!(
+            writer,
+            r#"<{} name="{}""#,
+            kind(file),
+            escape_markup(&file.name_lossy())
+        )?;
+        if let FileType::Symlink { target, .. } = file.ftype() {
+            write!(
+                writer,
+                r#" target="{}""#,
+                escape_markup(&target.to_string_lossy())
+            )?;
+        }
+        let details = &self.details;
+        if details.permissions {
+            write!(
+                writer,
+                r#" mode="{:04o}" prot="{}""#,
+                file.mode(),
+                details::permissions(file)
+            )?;
+        }
+        if let Some(user) = details.user(file) {
+            write!(writer, r#" user="{}""#, escape_markup(&user))?;
+        }
+        if let Some(group) = details.group(file) {
+            write!(writer, r#" group="{}""#, escape_markup(&group))?;
+        }
+        if details.size.is_some() {
+            write!(writer, r#" size="{}""#, file.size())?;
+        }
+        if let Some(time) = details.time(file) {
+            write!(writer, r#" time="{}""#, escape_markup(&time))?;
+        }
+        if let Some(status) = details.git(file) {
+            write!(writer, r#" git="{}""#, escape_markup(&status))?;
+        }
+        write!(writer, ">")?;
+
+        if pos.descend {
+            writeln!(writer)
+        } else {
+            writeln!(writer, "</{}>", kind(file))
+        }
+    }
+
+    fn leave(&self, writer: &mut dyn Write, file: &File, pos: &Position) -> io::Result<()> {
+        indent(writer, pos.depth)?;
+        writeln!(writer, "</{}>", kind(file))
+    }
+
+    fn elided(&self, writer: &mut dyn Write, elided: &Elided, pos: &Position) -> io::Result<()> {
+        indent(writer, pos.depth)?;
+        writeln!(
+            writer,
+            r#"<elided directories="{}" files="{}"></elided>"#,
+            elided.directories, elided.files
+        )
+    }
+
+    fn error(&self, writer: &mut dyn Write, error: &Error, pos: &Position) -> io::Result<()> {
+        indent(writer, pos.depth)?;
+        writeln!(
+            writer,
+            "<error>{}</error>",
+            escape_markup(&error.to_string())
+        )
+    }
+
+    fn summary(&self, writer: &mut dyn Write, stats: &Stats) -> io::Result<()> {
+        indent(writer, 0)?;
+        writeln!(writer, "<report>")?;
+        indent(writer, 1)?;
+        writeln!(writer, "<directories>{}</directories>", stats.directories)?;
+        indent(writer, 1)?;
+        writeln!(writer, "<files>{}</files>", stats.non_directories())?;
+        indent(writer, 0)?;
+        writeln!(writer, "</report>")?;
+        writeln!(writer, "</tree>")
+    }
+}
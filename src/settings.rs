@@ -0,0 +1,276 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs, iter,
+    path::{Path, PathBuf},
+    slice,
+};
+
+use crossterm::style::ContentStyle;
+use toml::{Table, Value};
+
+use crate::{
+    color, config,
+    error::{describe, Error},
+    matcher::Matcher,
+};
+
+/// Icons and colors from the config file, which take precedence over the built-in ones
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Overrides {
+    pub(crate) icons: Matcher<String>,
+    pub(crate) colors: Matcher<ContentStyle>,
+}
+
+/// The contents of the config file, like
+///
+/// ```toml
+/// [options]
+/// dirs-first = true
+/// exclude = ["target", "node_modules"]
+///
+/// [icons]
+/// name = { Justfile = "🤖" }
+/// glob = { "*.test.ts" = "🧪" }
+///
+/// [colors]
+/// extension = { rs = "38;5;208" }
+/// ```
+///
+/// Options are named like the long options and apply unless the command line gives the same or
+/// a conflicting option. Icons and colors are given by `name`, `extension` or `glob` with colors
+/// in the format of `LS_COLORS`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Settings {
+    /// The arguments of each option by its long name
+    pub(crate) options: Vec<(String, Vec<OsString>)>,
+    pub(crate) overrides: Overrides,
+}
+
+impl Settings {
+    /// Reads `MTREE_CONFIG` if set, or else `mtree/config.toml` in the config directory if it exists
+    pub(crate) fn load() -> Result<Settings, Error> {
+        if let Some(path) = env::var_os("MTREE_CONFIG").filter(|path| !path.is_empty()) {
+            return Settings::read(Path::new(&path));
+        }
+        match default_path() {
+            Some(path) if path.exists() => Settings::read(&path),
+            _ => Ok(Settings::default()),
+        }
+    }
+
+    fn read(path: &Path) -> Result<Settings, Error> {
+        let error = |message| Error::Config {
+            path: path.to_path_buf(),
+            message,
+        };
+        let content = fs::read_to_string(path).map_err(|e| error(describe(&e)))?;
+        let settings = Settings::parse(&content).map_err(error)?;
+
+        // Report unknown options here rather than as if they were on the command line
+        let args = settings.options.iter().flat_map(|(_, args)| args).cloned();
+        let args = iter::once(OsString::from("mtree")).chain(args);
+        if let Err(e) = config::command().try_get_matches_from(args) {
+            let message = e.to_string();
+            let line = message.lines().next().unwrap_or_default();
+            return Err(error(line.trim_start_matches("error: ").to_owned()));
+        }
+        Ok(settings)
+    }
+
+    fn parse(content: &str) -> Result<Settings, String> {
+        let table: Table = content
+            .parse()
+            .map_err(|e: toml::de::Error| e.message().to_owned())?;
+
+        let mut settings = Settings::default();
+        for (key, value) in &table {
+            let Value::Table(table) = value else {
+                return Err(format!("`{}` is not a table", key));
+            };
+            match key.as_str() {
+                "options" => settings.options = options(table)?,
+                "icons" => {
+                    let icon = |value: &Value| value.as_str().map(str::to_owned);
+                    settings.overrides.icons = matcher(table, icon)?;
+                }
+                "colors" => {
+                    let style = |value: &Value| value.as_str().and_then(color::parse_sgr);
+                    settings.overrides.colors = matcher(table, style)?;
+                }
+                _ => return Err(format!("unknown table `{}`", key)),
+            }
+        }
+        Ok(settings)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/mtree/config.toml`, with `~/.config` as the default config directory
+fn default_path() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(dir.join("mtree").join("config.toml"))
+}
+
+/// Turns `name = value` into `--name=value` and lists into repeated options
+fn options(table: &Table) -> Result<Vec<(String, Vec<OsString>)>, String> {
+    let mut options = Vec::new();
+    for (name, value) in table {
+        let values = match value {
+            Value::Array(values) => values.as_slice(),
+            value => slice::from_ref(value),
+        };
+        let mut args = Vec::new();
+        for value in values {
+            match value {
+                Value::Boolean(value) => args.push(format!("--{}={}", name, value)),
+                Value::String(value) => args.push(format!("--{}={}", name, value)),
+                Value::Integer(value) => args.push(format!("--{}={}", name, value)),
+                _ => return Err(format!("unsupported value for option `{}`", name)),
+            }
+        }
+        options.push((name.clone(), args.into_iter().map(OsString::from).collect()));
+    }
+    Ok(options)
+}
+
+/// Reads the `name`, `extension` and `glob` tables of `table`, whose values `parse` converts
+fn matcher<T>(table: &Table, parse: impl Fn(&Value) -> Option<T>) -> Result<Matcher<T>, String> {
+    let mut matcher = Matcher::default();
+    for (kind, entries) in table {
+        let Value::Table(entries) = entries else {
+            return Err(format!("`{}` is not a table", kind));
+        };
+        for (key, value) in entries {
+            let value = parse(value).ok_or_else(|| format!("invalid value for `{}`", key))?;
+            match kind.as_str() {
+                "name" => matcher.insert_name(key, value),
+                "extension" => matcher.insert_extension(key, value),
+                "glob" => matcher.insert_glob(key, value).map_err(|e| e.to_string())?,
+                _ => {
+                    return Err(format!(
+                        "unknown table `{}`, expected name, extension or glob",
+                        kind
+                    ))
+                }
+            }
+        }
+    }
+    Ok(matcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::TempDir, Config};
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn parse_test() {
+        let settings = Settings::parse(
+            r#"
+[options]
+dirs-first = true
+all = false
+limit = 2
+exclude = ["target", "*.log"]
+
+[icons]
+name = { Justfile = "J" }
+glob = { "*.test.ts" = "T" }
+
+[colors]
+extension = { rs = "31" }
+"#,
+        )
+        .unwrap();
+
+        let options: Vec<_> = settings
+            .options
+            .iter()
+            .map(|(name, args)| (name.as_str(), args.join(OsStr::new(" "))))
+            .collect();
+        assert_eq!(
+            options,
+            [
+                ("dirs-first", "--dirs-first=true".into()),
+                ("all", "--all=false".into()),
+                ("limit", "--limit=2".into()),
+                ("exclude", "--exclude=target --exclude=*.log".into()),
+            ]
+        );
+        let icons = &settings.overrides.icons;
+        assert_eq!(icons.get("Justfile").map(String::as_str), Some("J"));
+        assert_eq!(icons.get("a.test.ts").map(String::as_str), Some("T"));
+        assert_eq!(icons.get("a.ts"), None);
+        let colors = &settings.overrides.colors;
+        assert_eq!(colors.get("main.rs").copied(), color::parse_sgr("31"));
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let error = |content| Settings::parse(content).unwrap_err();
+        assert_eq!(error("options = 1"), "`options` is not a table");
+        assert_eq!(error("[theme]"), "unknown table `theme`");
+        assert_eq!(
+            error("[options]\nlimit = 1.5"),
+            "unsupported value for option `limit`"
+        );
+        assert_eq!(
+            error("[icons]\nsuffix = { rs = \"R\" }"),
+            "unknown table `suffix`, expected name, extension or glob"
+        );
+        assert_eq!(error("[colors]\nname = { a = 1 }"), "invalid value for `a`");
+        assert!(Settings::parse("[options").is_err());
+    }
+
+    #[test]
+    fn read_test() {
+        let tmp = TempDir::new("config");
+        tmp.file("config.toml", "[options]\nlimit = 1\nbogus = true\n");
+        let path = tmp.path().join("config.toml");
+        let error = Settings::read(&path).unwrap_err().to_string();
+        assert_eq!(
+            error,
+            format!(
+                "error in config file {}: unexpected argument '--bogus' found",
+                path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn command_line_test() {
+        let settings = Settings::parse(
+            "[options]\nlimit = 1\ndirs-first = true\nsort = \"size\"\nall = true\nprune = true\n",
+        )
+        .unwrap();
+        let config = |args: &[&str]| {
+            let args: Vec<OsString> = iter::once("mtree")
+                .chain(args.iter().copied())
+                .map(OsString::from)
+                .collect();
+            let given = config::command().try_get_matches_from(&args)?;
+            Config::with_settings(&args, &given, settings.clone())
+        };
+
+        let config_only = config(&[]).unwrap();
+        assert_eq!(config_only.limit, 1);
+        assert!(config_only.dirs_first && config_only.all && config_only.prune);
+
+        // The same option, a conflicting one or a flag turned off on the command line wins
+        let overridden = config(&["-l", "2", "--dirs-last", "--all=false"]).unwrap();
+        assert_eq!(overridden.limit, 2);
+        assert!(!overridden.dirs_first && overridden.dirs_last && !overridden.all);
+        assert!(overridden.prune);
+        let unsorted = config(&["--unsorted"]).unwrap();
+        assert!(unsorted.unsorted && !unsorted.dirs_first && !unsorted.prune);
+        assert_eq!(unsorted.sort, crate::sort::SortBy::Name);
+
+        // Conflicts on the command line itself are still errors
+        assert!(config(&["--dirs-first", "--dirs-last"]).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use std::{cmp::Ordering, path::Path};
+
+use clap::ValueEnum;
+
+use crate::file::{File, FileType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortBy {
+    /// Alphabetically, ignoring case
+    Name,
+    /// Like name, but numbers within names are compared by value
+    Version,
+    /// Largest first
+    Size,
+    /// Oldest modification first
+    Mtime,
+    /// Oldest status change first
+    Ctime,
+    /// By extension, then name
+    Extension,
+    /// Directories, files, symlinks, then special files
+    Type,
+    /// In the order the file system returns them
+    None,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirOrder {
+    Mixed,
+    First,
+    Last,
+}
+
+/// Orders the entries of a directory
+#[derive(Debug, Clone, Copy)]
+pub struct Sorter {
+    by: SortBy,
+    reverse: bool,
+    dirs: DirOrder,
+}
+
+impl Sorter {
+    pub fn new(by: SortBy, reverse: bool, dirs: DirOrder) -> Self {
+        Self { by, reverse, dirs }
+    }
+
+    /// Sorts `items` by the file `file` returns for each of them
+    pub fn sort<T>(&self, items: &mut [T], file: impl Fn(&T) -> &File) {
+        items.sort_by(|a, b| self.compare(file(a), file(b)));
+    }
+
+    pub fn compare(&self, a: &File, b: &File) -> Ordering {
+        let dirs = match self.dirs {
+            DirOrder::Mixed => Ordering::Equal,
+            DirOrder::First => b.is_dir().cmp(&a.is_dir()),
+            DirOrder::Last => a.is_dir().cmp(&b.is_dir()),
+        };
+
+        let (name_a, name_b) = (a.name_lossy(), b.name_lossy());
+        let (a_name, b_name) = (name_a.as_ref(), name_b.as_ref());
+        let order = match self.by {
+            SortBy::Name => name_cmp(a_name, b_name),
+            SortBy::Version => version_cmp(a_name, b_name),
+            SortBy::Size => b
+                .size()
+                .cmp(&a.size())
+                .then_with(|| name_cmp(a_name, b_name)),
+            SortBy::Mtime => a
+                .modified()
+                .cmp(&b.modified())
+                .then_with(|| name_cmp(a_name, b_name)),
+            SortBy::Ctime => a
+                .changed()
+                .cmp(&b.changed())
+                .then_with(|| name_cmp(a_name, b_name)),
+            SortBy::Extension => extension(a_name)
+                .cmp(extension(b_name))
+                .then_with(|| name_cmp(a_name, b_name)),
+            SortBy::Type => type_rank(a.ftype())
+                .cmp(&type_rank(b.ftype()))
+                .then_with(|| name_cmp(a_name, b_name)),
+            SortBy::None => Ordering::Equal,
+        };
+
+        dirs.then(if self.reverse { order.reverse() } else { order })
+    }
+}
+
+fn extension(name: &str) -> &str {
+    Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+}
+
+fn type_rank(ftype: &FileType) -> u8 {
+    match ftype {
+        FileType::Directory => 0,
+        FileType::File { .. } => 1,
+        FileType::Symlink { .. } => 2,
+        FileType::BlockDevice => 3,
+        FileType::CharDevice => 4,
+        FileType::Pipe => 5,
+        FileType::Socket => 6,
+        FileType::Special => 7,
+    }
+}
+
+/// Case-insensitive, with byte order to break ties
+fn name_cmp(a: &str, b: &str) -> Ordering {
+    caseless_cmp(a, b).then_with(|| a.cmp(b))
+}
+
+fn caseless_cmp(a: &str, b: &str) -> Ordering {
+    let lower_a = a.chars().flat_map(char::to_lowercase);
+    let lower_b = b.chars().flat_map(char::to_lowercase);
+    lower_a.cmp(lower_b)
+}
+
+/// Compares runs of digits by their value and everything else ignoring case
+///
+/// So `file9` sorts before `file10` and `v1.2.10` after `v1.2.9`.
+fn version_cmp(a: &str, b: &str) -> Ordering {
+    let (mut rest_a, mut rest_b) = (a, b);
+
+    while !rest_a.is_empty() && !rest_b.is_empty() {
+        let (chunk_a, tail_a) = split_chunk(rest_a);
+        let (chunk_b, tail_b) = split_chunk(rest_b);
+
+        let digits = |s: &str| s.starts_with(|c: char| c.is_ascii_digit());
+        let order = if digits(chunk_a) && digits(chunk_b) {
+            let trimmed_a = chunk_a.trim_start_matches('0');
+            let trimmed_b = chunk_b.trim_start_matches('0');
+            trimmed_a
+                .len()
+                .cmp(&trimmed_b.len())
+                .then_with(|| trimmed_a.cmp(trimmed_b))
+        } else {
+            caseless_cmp(chunk_a, chunk_b)
+        };
+
+        if order != Ordering::Equal {
+            return order;
+        }
+        rest_a = tail_a;
+        rest_b = tail_b;
+    }
+
+    rest_a.len().cmp(&rest_b.len()).then_with(|| name_cmp(a, b))
+}
+
+/// Splits off the leading run of either digits or non-digits
+fn split_chunk(s: &str) -> (&str, &str) {
+    let is_digit = s.starts_with(|c: char| c.is_ascii_digit());
+    let end = s
+        .find(|c: char| c.is_ascii_digit() != is_digit)
+        .unwrap_or(s.len());
+    s.split_at(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn sorted(mut names: Vec<&str>, cmp: fn(&str, &str) -> Ordering) -> Vec<&str> {
+        names.sort_by(|a, b| cmp(a, b));
+        names
+    }
+
+    #[test]
+    fn name_cmp_test() {
+        assert_eq!(
+            sorted(vec!["b", "Cargo.toml", "a", "C", "c"], name_cmp),
+            vec!["a", "b", "C", "c", "Cargo.toml"]
+        );
+    }
+
+    #[test]
+    fn version_cmp_test() {
+        assert_eq!(
+            sorted(
+                vec!["file10", "file9", "file1", "file", "v1.2.10", "v1.2.9", "file09"],
+                version_cmp
+            ),
+            vec!["file", "file1", "file09", "file9", "file10", "v1.2.9", "v1.2.10"]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt::Display;
+
+use crate::file::{File, FileType};
+
+/// Counts of everything encountered while walking a tree.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub directories: u64,
+    pub files: u64,
+    pub executables: u64,
+    pub symlinks: u64,
+    pub broken_symlinks: u64,
+    pub block_devices: u64,
+    pub char_devices: u64,
+    pub pipes: u64,
+    pub sockets: u64,
+    pub special: u64,
+    /// Sum of the sizes of all non-directory entries
+    pub bytes: u64,
+    /// Entries rejected by the entry predicate
+    pub skipped: u64,
+    /// Directories and entries which could not be read
+    pub errors: u64,
+}
+
+impl Stats {
+    pub fn record(&mut self, file: &File) {
+        match file.ftype() {
+            FileType::File { exec: true } => self.executables += 1,
+            FileType::File { .. } => self.files += 1,
+            FileType::Directory => self.directories += 1,
+            FileType::Symlink { valid: true, .. } => self.symlinks += 1,
+            FileType::Symlink { .. } => self.broken_symlinks += 1,
+            FileType::BlockDevice => self.block_devices += 1,
+            FileType::CharDevice => self.char_devices += 1,
+            FileType::Pipe => self.pipes += 1,
+            FileType::Socket => self.sockets += 1,
+            FileType::Special => self.special += 1,
+        }
+
+        if !matches!(file.ftype(), FileType::Directory) {
+            self.bytes += file.size();
+        }
+    }
+
+    /// Adds the counts of `other`, e.g. of a subtree walked separately
+    pub fn merge(&mut self, other: &Stats) {
+        self.directories += other.directories;
+        self.files += other.files;
+        self.executables += other.executables;
+        self.symlinks += other.symlinks;
+        self.broken_symlinks += other.broken_symlinks;
+        self.block_devices += other.block_devices;
+        self.char_devices += other.char_devices;
+        self.pipes += other.pipes;
+        self.sockets += other.sockets;
+        self.special += other.special;
+        self.bytes += other.bytes;
+        self.skipped += other.skipped;
+        self.errors += other.errors;
+    }
+
+    pub fn skip(&mut self) {
+        self.skipped += 1;
+    }
+
+    /// Number of entries that are not directories
+    pub fn non_directories(&self) -> u64 {
+        self.files
+            + self.executables
+            + self.symlinks
+            + self.broken_symlinks
+            + self.block_devices
+            + self.char_devices
+            + self.pipes
+            + self.sockets
+            + self.special
+    }
+}
+
+pub(crate) fn plural(count: u64, singular: &str, plural: &str) -> String {
+    format!("{} {}", count, if count == 1 { singular } else { plural })
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} and {}",
+            plural(self.directories, "directory", "directories"),
+            plural(self.non_directories(), "file", "files")
+        )?;
+
+        // Only mention the details which actually occurred
+        let details: Vec<String> = [
+            (self.executables, "executable", "executables"),
+            (self.symlinks, "symlink", "symlinks"),
+            (self.broken_symlinks, "broken symlink", "broken symlinks"),
+            (self.block_devices, "block device", "block devices"),
+            (self.char_devices, "character device", "character devices"),
+            (self.pipes, "pipe", "pipes"),
+            (self.sockets, "socket", "sockets"),
+            (self.special, "special file", "special files"),
+            (self.bytes, "byte", "bytes"),
+            (self.skipped, "skipped", "skipped"),
+            (self.errors, "error", "errors"),
+        ]
+        .into_iter()
+        .filter(|(count, ..)| *count > 0)
+        .map(|(count, singular, plural_)| plural(count, singular, plural_))
+        .collect();
+
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn display_test() {
+        let mut stats = Stats {
+            directories: 6,
+            files: 3,
+            ..Default::default()
+        };
+        assert_eq!(stats.to_string(), "6 directories and 3 files");
+
+        stats.directories = 1;
+        stats.files = 0;
+        stats.executables = 1;
+        stats.broken_symlinks = 2;
+        stats.bytes = 512;
+        stats.errors = 1;
+        assert_eq!(
+            stats.to_string(),
+            "1 directory and 3 files (1 executable, 2 broken symlinks, 512 bytes, 1 error)"
+        );
+    }
+}
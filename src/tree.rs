@@ -1,104 +1,725 @@
 use std::{
     fs::{self, DirEntry},
     io::{self, Write},
-    path::PathBuf,
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
+    path::{Path, PathBuf},
 };
 
-use crate::{file::File, Config};
+use rayon::{
+    iter::{IntoParallelIterator, ParallelIterator},
+    ThreadPool, ThreadPoolBuilder,
+};
 
-const PREFIX: &str = "│   ";
-const PREFIX_LAST: &str = "    ";
-const FRONT: &str = "├──";
-const FRONT_LAST: &str = "└──";
+use crate::{
+    details::{Details, Names, SizeUnits, TimeFormat},
+    error::Error,
+    file::{Appearance, File},
+    filter::Filter,
+    git::GitStatus,
+    gitignore::GitIgnore,
+    render::{
+        Elided, HtmlRenderer, JsonRenderer, Output, Position, Renderer, TextRenderer, XmlRenderer,
+    },
+    sort::Sorter,
+    stats::Stats,
+    Config,
+};
 
-pub fn tree(config: Config) -> io::Result<()> {
+/// Lists the tree described by `config` on stdout
+pub fn run(config: Config) -> Result<Stats, Error> {
     let tree = Tree::new(config);
-    tree.tree(&mut io::stdout())
+    let mut stdout = io::stdout();
+    let stats = tree.tree(&mut stdout)?;
+    tree.summary(&mut stdout, &stats)?;
+    Ok(stats)
 }
 
 struct State<'a> {
     depth: u32,
     dir: PathBuf,
-    prefix: &'a str,
+    ignore: Option<&'a GitIgnore<'a>>,
+    /// Whether an ancestor matched an include pattern, which lists everything below it
+    matched: bool,
+    /// The device and inode of `dir`, to recognize symlinks leading back up
+    id: Option<(u64, u64)>,
+    parent: Option<&'a State<'a>>,
 }
 
 impl<'a> State<'a> {
-    pub fn new(dir: PathBuf, depth: u32, prefix: &'a str) -> Self {
-        Self { dir, depth, prefix }
+    pub fn new(dir: PathBuf, depth: u32, ignore: Option<&'a GitIgnore<'a>>, matched: bool) -> Self {
+        Self {
+            id: dir_id(&dir),
+            dir,
+            depth,
+            ignore,
+            matched,
+            parent: None,
+        }
+    }
+
+    /// The state for the subdirectory at `path`
+    fn child<'b>(
+        &'b self,
+        path: PathBuf,
+        ignore: Option<&'b GitIgnore<'b>>,
+        matched: bool,
+    ) -> State<'b> {
+        State {
+            parent: Some(self),
+            ..State::new(path, self.depth + 1, ignore, matched)
+        }
+    }
+
+    /// Whether this directory is an ancestor of itself, which following symlinks can lead to
+    fn is_recursive(&self) -> bool {
+        let mut ancestor = self.parent;
+        while let Some(state) = ancestor {
+            if self.id.is_some() && state.id == self.id {
+                return true;
+            }
+            ancestor = state.parent;
+        }
+        false
+    }
+}
+
+fn dir_id(dir: &Path) -> Option<(u64, u64)> {
+    fs::metadata(dir).ok().map(|m| (m.dev(), m.ino()))
+}
+
+/// An entry which passed all filters
+pub(crate) struct Node {
+    pub(crate) file: File,
+    /// Whether the children were read, false for files and directories at the depth limit
+    pub(crate) descend: bool,
+    pub(crate) children: Vec<Node>,
+    /// The children left out by `--max-entries`
+    pub(crate) elided: Option<Elided>,
+    /// Problems reading the children, shown after them
+    pub(crate) errors: Vec<Error>,
+}
+
+/// What was read from a directory
+#[derive(Default)]
+struct Contents {
+    nodes: Vec<Node>,
+    elided: Option<Elided>,
+    errors: Vec<Error>,
+}
+
+impl Contents {
+    /// Whether nothing at all is shown for the directory
+    fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.elided.is_none() && self.errors.is_empty()
+    }
+
+    /// The total size of the entries, including elided ones
+    fn size(&self) -> u64 {
+        let elided = self.elided.as_ref().map_or(0, |e| e.size);
+        self.nodes.iter().map(|n| n.file.size()).sum::<u64>() + elided
+    }
+}
+
+/// The renderer for the output format, colors, icons and columns of `config`
+fn renderer(config: &Config) -> Box<dyn Renderer + Sync> {
+    let icons = config.icons();
+
+    let details = Details {
+        permissions: config.permissions,
+        users: config.user.then(Names::users),
+        groups: config.group.then(Names::groups),
+        time: match (&config.timefmt, config.date) {
+            (Some(format), _) => Some(TimeFormat::Custom(format.clone())),
+            (None, true) => Some(TimeFormat::Default),
+            (None, false) => None,
+        },
+        size: if config.si {
+            Some(SizeUnits::Si)
+        } else if config.human {
+            Some(SizeUnits::Iec)
+        } else if config.size || config.du {
+            Some(SizeUnits::Bytes)
+        } else {
+            None
+        },
+        git: config.git.then(|| GitStatus::new(&config.directories)),
+    };
+
+    match config.output {
+        Output::Text => Box::new(TextRenderer::new(
+            Appearance {
+                colors: config.colors(config.color.enabled()),
+                icons,
+                escape: config.escape,
+                literal: config.literal,
+                resolve: config.resolve,
+                full_path: config.full_path || config.absolute,
+            },
+            details,
+            !config.noindent,
+        )),
+        Output::Json => Box::new(JsonRenderer),
+        Output::Xml => Box::new(XmlRenderer::new(details)),
+        Output::Html => Box::new(HtmlRenderer::new(
+            icons,
+            details,
+            config.base_href.clone(),
+            config.directories.clone(),
+        )),
     }
 }
 
-struct Tree {
+/// Decides which files are listed in addition to the options
+pub(crate) type Predicate = Box<dyn Fn(&File) -> bool + Send + Sync>;
+
+pub(crate) struct Tree {
     config: Config,
+    filter: Filter,
+    sorter: Sorter,
+    /// `None` for trees which are only read
+    renderer: Option<Box<dyn Renderer + Sync>>,
+    /// The threads to read directories with, `None` to read them one after another
+    pool: Option<ThreadPool>,
+    predicate: Option<Predicate>,
 }
 
 impl Tree {
-    pub fn new(config: Config) -> Self {
-        Self { config }
+    /// A tree which is written in the format of `config`
+    pub(crate) fn new(config: Config) -> Self {
+        let mut tree = Tree::reader(config);
+        tree.renderer = Some(renderer(&tree.config));
+        tree
+    }
+
+    /// A tree which is only read, without the colors, icons and columns of the output
+    pub(crate) fn reader(mut config: Config) -> Self {
+        // All paths are below a root, so they are canonical if it is
+        if config.absolute {
+            for root in &mut config.directories {
+                if let Ok(canonical) = fs::canonicalize(&root) {
+                    *root = canonical;
+                }
+            }
+        }
+
+        let filter = Filter::new(
+            config.include.clone(),
+            config.exclude.clone(),
+            config.matchdirs,
+        );
+
+        let sorter = config.sorter();
+
+        // Fall back to a single thread if no more can be started
+        let pool = (config.threads != 1)
+            .then(|| {
+                ThreadPoolBuilder::new()
+                    .num_threads(config.threads)
+                    .build()
+                    .ok()
+            })
+            .flatten();
+
+        Self {
+            config,
+            filter,
+            sorter,
+            renderer: None,
+            pool,
+            predicate: None,
+        }
+    }
+
+    fn renderer(&self) -> &(dyn Renderer + Sync) {
+        self.renderer
+            .as_deref()
+            .expect("Only trees built with Tree::new are written")
+    }
+
+    /// Only lists the files `predicate` accepts besides the configured filters
+    pub(crate) fn with_predicate(mut self, predicate: Predicate) -> Self {
+        self.predicate = Some(predicate);
+        self
+    }
+
+    /// Writes one tree per root, reporting roots which cannot be read inline
+    fn tree(&self, writer: &mut dyn Write) -> Result<Stats, Error> {
+        let mut stats = Stats::default();
+        self.renderer().begin(writer)?;
+
+        let roots = &self.config.directories;
+        for (i, root) in roots.iter().enumerate() {
+            let is_last = i + 1 == roots.len();
+            let result = if self.config.unsorted {
+                self.stream_root(writer, root, is_last)
+            } else {
+                self.build(root).and_then(|(node, stats)| {
+                    self.render(writer, &node, 0, is_last, &mut Vec::new())?;
+                    Ok(stats)
+                })
+            };
+
+            match result {
+                Ok(root_stats) => stats.merge(&root_stats),
+                Err(e @ Error::Output(_)) => return Err(e),
+                Err(e) => {
+                    stats.errors += 1;
+                    let pos = Position {
+                        depth: 0,
+                        is_last,
+                        ancestors: &[],
+                        descend: false,
+                    };
+                    self.renderer().error(writer, &e, &pos)?;
+                }
+            }
+        }
+
+        Ok(stats)
     }
 
-    fn tree<W: Write>(&self, writer: &mut W) -> io::Result<()> {
-        let state = State::new(self.config.directory.clone(), 0, "");
-        self.tree_rec(writer, state)
+    /// Reads the whole tree below `root` into memory
+    pub(crate) fn build(&self, root: &Path) -> Result<(Node, Stats), Error> {
+        let mut stats = Stats::default();
+        let mut file = File::root(root).map_err(|e| Error::root(root, e))?;
+
+        // Files given as roots are listed on their own
+        if !root.is_dir() {
+            stats.record(&file);
+            let node = Node {
+                file,
+                descend: false,
+                children: Vec::new(),
+                elided: None,
+                errors: Vec::new(),
+            };
+            return Ok((node, stats));
+        }
+
+        let ignore = self.ignore(root);
+        let state = State::new(root.to_path_buf(), 0, ignore.as_ref(), false);
+        let contents = match &self.pool {
+            Some(pool) => pool.install(|| self.collect(state, &mut stats)),
+            None => self.collect(state, &mut stats),
+        };
+        if self.config.du {
+            file.set_size(file.size() + contents.size());
+        }
+        let root = Node {
+            file,
+            descend: true,
+            children: contents.nodes,
+            elided: contents.elided,
+            errors: contents.errors,
+        };
+
+        Ok((root, stats))
+    }
+
+    /// Writes the tree below `root` while reading it, see [`Tree::stream`]
+    fn stream_root(
+        &self,
+        writer: &mut dyn Write,
+        root: &Path,
+        is_last: bool,
+    ) -> Result<Stats, Error> {
+        let mut stats = Stats::default();
+        let file = File::root(root).map_err(|e| Error::root(root, e))?;
+
+        let descend = root.is_dir();
+        let pos = Position {
+            depth: 0,
+            is_last,
+            ancestors: &[],
+            descend,
+        };
+        self.renderer().entry(writer, &file, &pos)?;
+        if !descend {
+            stats.record(&file);
+            return Ok(stats);
+        }
+
+        let ignore = self.ignore(root);
+        let state = State::new(root.to_path_buf(), 0, ignore.as_ref(), false);
+        self.stream(writer, state, &mut Vec::new(), &mut stats)?;
+        self.renderer().leave(writer, &file, &pos)?;
+
+        Ok(stats)
     }
 
-    fn tree_rec<W: Write>(&self, writer: &mut W, state: State) -> io::Result<()> {
-        if state.depth == 0 {
-            let file = File::from_path(&state.dir)?;
-            writeln!(writer, "{}", file).expect("Unable to write");
+    /// The ignore rules for `root`, if git's ignore files are respected
+    fn ignore(&self, root: &Path) -> Option<GitIgnore<'static>> {
+        match (self.config.gitignore, self.config.no_gitignore) {
+            (_, true) => None,
+            (explicit, false) => GitIgnore::new(root, !explicit),
         }
+    }
+
+    fn summary(&self, writer: &mut dyn Write, stats: &Stats) -> io::Result<()> {
+        self.renderer().summary(writer, stats)
+    }
 
+    /// Reads the entries of a directory which pass all filters, sorted and with their children
+    ///
+    /// Entries which cannot be read are left out and reported in the errors instead.
+    fn collect(&self, state: State, stats: &mut Stats) -> Contents {
+        let mut errors = Vec::new();
         if state.depth >= self.config.limit {
-            return Ok(());
+            return Contents::default();
+        }
+
+        let mut entries = Vec::new();
+        match fs::read_dir(&state.dir) {
+            Ok(read_dir) => {
+                for entry in read_dir {
+                    match entry {
+                        Ok(entry) if self.entry_predicate(&entry, &state) => entries.push(entry),
+                        Ok(_) => stats.skip(),
+                        Err(e) => errors.push(Error::read_dir(e)),
+                    }
+                }
+            }
+            Err(e) => errors.push(Error::read_dir(e)),
+        }
+
+        if let Some(limit) = self.config.filelimit {
+            if entries.len() > limit {
+                errors.push(Error::FileLimit(entries.len()));
+                entries.clear();
+            }
+        }
+
+        // Every entry is visited with its own stats, so the entries can be visited in parallel
+        let visit = |entry| {
+            let mut stats = Stats::default();
+            let visited = self.visit(entry, &state, &mut stats);
+            (visited, stats)
+        };
+        let visited: Vec<_> = if self.pool.is_some() {
+            entries.into_par_iter().map(visit).collect()
+        } else {
+            entries.into_iter().map(visit).collect()
+        };
+
+        let mut nodes = Vec::with_capacity(visited.len());
+        for (node, entry_stats) in visited {
+            stats.merge(&entry_stats);
+            match node {
+                Ok(node) => nodes.extend(node),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        stats.errors += errors.iter().filter(|e| e.is_failure()).count() as u64;
+        self.sorter.sort(&mut nodes, |n| &n.file);
+
+        // The elided entries still count, in the stats and in the size of the directory
+        let elided = match self.config.max_entries {
+            Some(max) if nodes.len() > max => Some(Elided::new(nodes.drain(max..).map(|n| n.file))),
+            _ => None,
+        };
+
+        Contents {
+            nodes,
+            elided,
+            errors,
+        }
+    }
+
+    /// Reads a single entry of a directory and its children, `None` if it is pruned
+    fn visit(
+        &self,
+        entry: DirEntry,
+        state: &State,
+        stats: &mut Stats,
+    ) -> Result<Option<Node>, Error> {
+        let path = entry.path();
+        let mut file = File::from_path(&path).map_err(|e| Error::metadata(&path, e))?;
+        if self.predicate.as_ref().is_some_and(|accept| !accept(&file)) {
+            stats.skip();
+            return Ok(None);
         }
 
-        let mut entries: Vec<DirEntry> = fs::read_dir(&state.dir)?
-            .filter_map(|e| if let Ok(e) = e { Some(e) } else { None })
+        let is_dir = entry.file_type().is_ok_and(|t| self.is_dir(t, &path));
+        let descend = is_dir && state.depth + 1 < self.config.limit;
+        let mut contents = Contents::default();
+        let mut size = 0;
+        if descend || (is_dir && self.config.du) {
+            let name = entry.file_name();
+            let ignore = state.ignore.map(|i| i.child(&name));
+            let matched = state.matched || self.filter.includes_dir(&name);
+            let child = state.child(path, ignore.as_ref(), matched);
+
+            size = if child.is_recursive() {
+                contents.errors.push(Error::Recursive);
+                0
+            } else if descend {
+                contents = self.collect(child, stats);
+                if self.config.prune && contents.is_empty() {
+                    stats.skip();
+                    return Ok(None);
+                }
+                contents.size()
+            } else {
+                self.du(child)
+            };
+        }
+
+        // Before adding the contents, which are recorded on their own
+        stats.record(&file);
+        if self.config.du {
+            file.set_size(file.size() + size);
+        }
+        Ok(Some(Node {
+            file,
+            descend,
+            children: contents.nodes,
+            elided: contents.elided,
+            errors: contents.errors,
+        }))
+    }
+
+    /// The total size of everything below a directory beyond the depth limit
+    ///
+    /// Entries which cannot be read do not count towards the total.
+    fn du(&self, state: State) -> u64 {
+        let Ok(read_dir) = fs::read_dir(&state.dir) else {
+            return 0;
+        };
+
+        let mut total = 0;
+        for entry in read_dir
+            .filter_map(Result::ok)
             .filter(|e| self.entry_predicate(e, &state))
-            .collect();
-        entries.sort_by_key(|e| e.file_name());
-        let dir_limit = entries.len();
+        {
+            total += entry.metadata().map_or(0, |m| m.len());
 
-        for (i, entry) in entries.into_iter().enumerate() {
-            let is_last = i == (dir_limit - 1);
             let path = entry.path();
-            let file = File::from_path(&path)?;
+            if entry.file_type().is_ok_and(|t| self.is_dir(t, &path)) {
+                let name = entry.file_name();
+                let ignore = state.ignore.map(|i| i.child(&name));
+                let matched = state.matched || self.filter.includes_dir(&name);
+                let child = state.child(path, ignore.as_ref(), matched);
+                if !child.is_recursive() {
+                    total += self.du(child);
+                }
+            }
+        }
+        total
+    }
 
-            writeln!(
-                writer,
-                "{}{} {name:}",
-                state.prefix,
-                if is_last { FRONT_LAST } else { FRONT },
-                name = file,
-            )
-            .expect("Unable to write");
+    fn render(
+        &self,
+        writer: &mut dyn Write,
+        node: &Node,
+        depth: u32,
+        is_last: bool,
+        ancestors: &mut Vec<bool>,
+    ) -> io::Result<()> {
+        let pos = Position {
+            depth,
+            is_last,
+            ancestors,
+            descend: node.descend,
+        };
+        self.renderer().entry(writer, &node.file, &pos)?;
 
-            if path.is_dir() {
-                let mut prefix = String::from(state.prefix);
-                prefix.push_str(if is_last { PREFIX_LAST } else { PREFIX });
-                self.tree_rec(writer, State::new(path, state.depth + 1, &prefix))?;
+        if node.descend {
+            // The root is not drawn with connectors
+            if depth > 0 {
+                ancestors.push(is_last);
+            }
+            let more = node.elided.is_some() || !node.errors.is_empty();
+            let count = node.children.len();
+            for (i, child) in node.children.iter().enumerate() {
+                self.render(writer, child, depth + 1, i == count - 1 && !more, ancestors)?;
+            }
+            if let Some(elided) = &node.elided {
+                let pos = Position {
+                    depth: depth + 1,
+                    is_last: node.errors.is_empty(),
+                    ancestors,
+                    descend: false,
+                };
+                self.renderer().elided(writer, elided, &pos)?;
+            }
+            self.render_errors(writer, &node.errors, depth + 1, ancestors)?;
+            if depth > 0 {
+                ancestors.pop();
             }
+
+            let pos = Position {
+                depth,
+                is_last,
+                ancestors,
+                descend: node.descend,
+            };
+            self.renderer().leave(writer, &node.file, &pos)?;
         }
 
         Ok(())
     }
 
+    /// Whether the walk descends into an entry of type `file_type`
+    ///
+    /// Symlinks to directories are only followed with `--follow`.
+    fn is_dir(&self, file_type: fs::FileType, path: &Path) -> bool {
+        file_type.is_dir() || (self.config.follow && file_type.is_symlink() && path.is_dir())
+    }
+
+    /// Writes `errors` as the last entries of a directory
+    fn render_errors(
+        &self,
+        writer: &mut dyn Write,
+        errors: &[Error],
+        depth: u32,
+        ancestors: &[bool],
+    ) -> io::Result<()> {
+        for (i, error) in errors.iter().enumerate() {
+            let pos = Position {
+                depth,
+                is_last: i == errors.len() - 1,
+                ancestors,
+                descend: false,
+            };
+            self.renderer().error(writer, error, &pos)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the entries of a directory as they are read, for `--unsorted`
+    ///
+    /// Only the next entry is held back to know whether the current one is the last, so memory
+    /// does not grow with the size of directories.
+    fn stream(
+        &self,
+        writer: &mut dyn Write,
+        state: State,
+        ancestors: &mut Vec<bool>,
+        stats: &mut Stats,
+    ) -> io::Result<()> {
+        let mut errors = Vec::new();
+        let mut pending = None;
+
+        if state.depth < self.config.limit {
+            match fs::read_dir(&state.dir) {
+                Ok(read_dir) => {
+                    for entry in read_dir {
+                        match entry {
+                            Ok(entry) if self.entry_predicate(&entry, &state) => {
+                                if let Some(previous) = pending.replace(entry) {
+                                    let entry = (previous, false);
+                                    self.stream_entry(
+                                        writer,
+                                        entry,
+                                        &state,
+                                        ancestors,
+                                        stats,
+                                        &mut errors,
+                                    )?;
+                                }
+                            }
+                            Ok(_) => stats.skip(),
+                            Err(e) => errors.push(Error::read_dir(e)),
+                        }
+                    }
+                }
+                Err(e) => errors.push(Error::read_dir(e)),
+            }
+        }
+
+        if let Some(last) = pending {
+            let entry = (last, errors.is_empty());
+            self.stream_entry(writer, entry, &state, ancestors, stats, &mut errors)?;
+        }
+
+        stats.errors += errors.iter().filter(|e| e.is_failure()).count() as u64;
+        self.render_errors(writer, &errors, state.depth + 1, ancestors)
+    }
+
+    /// Writes a single entry of a directory followed by its children, see [`Tree::stream`]
+    fn stream_entry(
+        &self,
+        writer: &mut dyn Write,
+        (entry, is_last): (DirEntry, bool),
+        state: &State,
+        ancestors: &mut Vec<bool>,
+        stats: &mut Stats,
+        errors: &mut Vec<Error>,
+    ) -> io::Result<()> {
+        let path = entry.path();
+        let file = match File::from_path(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                errors.push(Error::metadata(path, e));
+                return Ok(());
+            }
+        };
+        stats.record(&file);
+
+        let is_dir = entry.file_type().is_ok_and(|t| self.is_dir(t, &path));
+        let descend = is_dir && state.depth + 1 < self.config.limit;
+        let pos = Position {
+            depth: state.depth + 1,
+            is_last,
+            ancestors,
+            descend,
+        };
+        self.renderer().entry(writer, &file, &pos)?;
+        if !descend {
+            return Ok(());
+        }
+
+        let name = entry.file_name();
+        let ignore = state.ignore.map(|i| i.child(&name));
+        let matched = state.matched || self.filter.includes_dir(&name);
+        let child = state.child(path, ignore.as_ref(), matched);
+
+        ancestors.push(is_last);
+        if child.is_recursive() {
+            self.render_errors(writer, &[Error::Recursive], child.depth + 1, ancestors)?;
+        } else {
+            self.stream(writer, child, ancestors, stats)?;
+        }
+        ancestors.pop();
+
+        let pos = Position {
+            depth: state.depth + 1,
+            is_last,
+            ancestors,
+            descend,
+        };
+        self.renderer().leave(writer, &file, &pos)
+    }
+
     fn entry_predicate(&self, entry: &DirEntry, state: &State) -> bool {
         if let Ok(meta) = entry.metadata() {
-            if self.config.directory_only && !meta.is_dir() {
+            let name = entry.file_name();
+            let is_dir = self.is_dir(meta.file_type(), &entry.path());
+
+            if self.config.directory_only && !is_dir {
                 return false;
             }
 
-            if !self.config.all
-                && entry
-                    .file_name()
-                    .to_str()
-                    .expect("Not valid UTF-8")
-                    .starts_with('.')
+            if !self.config.all && name.as_bytes().starts_with(b".") {
+                return false;
+            }
+
+            if state
+                .ignore
+                .is_some_and(|i| i.is_ignored(&name, meta.is_dir()))
             {
                 return false;
             }
 
+            if self.filter.excludes(&name) {
+                return false;
+            }
+
+            if !state.matched && !self.filter.includes(&name, is_dir) {
+                return false;
+            }
+
             true
         } else {
             false
@@ -109,15 +730,839 @@ impl Tree {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        color::ColorChoice, icons::IconSet, settings::Overrides, sort::SortBy, testing::TempDir,
+    };
+    use pretty_assertions::assert_eq;
+
+    /// The output without the line of the root, which has a varying name
+    fn render_below_root(config: Config) -> String {
+        let output = render(config);
+        output.split_once('\n').unwrap().1.to_owned()
+    }
+
+    fn get_default() -> Config {
+        Config {
+            directories: vec![PathBuf::from("test")],
+            all: false,
+            directory_only: false,
+            limit: 5,
+            color: ColorChoice::Never,
+            escape: false,
+            literal: false,
+            strict: false,
+            no_config: false,
+            overrides: Overrides::default(),
+            interactive: false,
+            follow: false,
+            resolve: false,
+            threads: 1,
+            unsorted: false,
+            filelimit: None,
+            max_entries: None,
+            full_path: false,
+            absolute: false,
+            noindent: false,
+            icons: IconSet::None,
+            no_icons: false,
+            output: Output::Text,
+            base_href: None,
+            gitignore: false,
+            no_gitignore: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            matchdirs: false,
+            prune: false,
+            sort: SortBy::Name,
+            reverse: false,
+            dirs_first: false,
+            dirs_last: false,
+            permissions: false,
+            user: false,
+            group: false,
+            date: false,
+            timefmt: None,
+            git: false,
+            size: false,
+            human: false,
+            si: false,
+            du: false,
+            help: None,
+        }
+    }
+
+    fn run(config: Config) -> Stats {
+        let mut output = Vec::new();
+        Tree::new(config).tree(&mut output).expect("Unable to walk")
+    }
+
+    fn render(config: Config) -> String {
+        let mut output = Vec::new();
+        Tree::new(config).tree(&mut output).expect("Unable to walk");
+        String::from_utf8(output).expect("Not UTF-8")
+    }
+
+    fn render_with_summary(config: Config) -> String {
+        let tree = Tree::new(config);
+        let mut output = Vec::new();
+        let stats = tree.tree(&mut output).expect("Unable to walk");
+        tree.summary(&mut output, &stats).expect("Unable to write");
+        String::from_utf8(output).expect("Not UTF-8")
+    }
+
+    #[test]
+    fn default_test() {
+        let expected = "test
+├── a
+│   └── b
+├── c.txt
+├── d
+│   ├── e
+│   │   └── f
+│   │       └── g
+│   └── h.txt
+└── d.txt
+";
+        assert_eq!(render(get_default()), expected);
+    }
+
+    #[test]
+    fn directory_test() {
+        let mut config = get_default();
+        config.directory_only = true;
+
+        let expected = "test
+├── a
+│   └── b
+└── d
+    └── e
+        └── f
+            └── g
+";
+        assert_eq!(render(config), expected);
+    }
 
     #[test]
-    fn tree_test() {
+    fn all_test() {
+        let mut config = get_default();
+        config.all = true;
+
+        let expected = "test
+├── a
+│   └── b
+│       └── .gitkeep
+├── c.txt
+├── d
+│   ├── e
+│   │   ├── .gitkeep
+│   │   └── f
+│   │       ├── .gitkeep
+│   │       └── g
+│   │           └── .gitkeep
+│   └── h.txt
+└── d.txt
+";
+        assert_eq!(render(config), expected);
+    }
+
+    #[test]
+    fn icons_test() {
+        let mut config = get_default();
+        config.icons = IconSet::Ascii;
+        config.limit = 1;
+
+        let expected = "+ test
+├── + a
+├── - c.txt
+├── + d
+└── - d.txt
+";
+        assert_eq!(render(config), expected);
+    }
+
+    #[test]
+    fn stats_test() {
+        let stats = run(get_default());
+        assert_eq!(stats.directories, 6);
+        assert_eq!(stats.files, 3);
+        assert_eq!(stats.skipped, 4);
+        assert_eq!(stats.to_string(), "6 directories and 3 files (4 skipped)");
+    }
+
+    #[test]
+    fn stats_all_test() {
+        let mut config = get_default();
+        config.all = true;
+
+        let stats = run(config);
+        assert_eq!(stats.to_string(), "6 directories and 7 files");
+    }
+
+    #[test]
+    fn stats_directory_only_test() {
+        let mut config = get_default();
+        config.directory_only = true;
+
+        let stats = run(config);
+        assert_eq!(stats.directories, 6);
+        assert_eq!(stats.non_directories(), 0);
+        assert_eq!(stats.skipped, 7);
+    }
+
+    #[test]
+    fn json_test() {
+        let mut config = get_default();
+        config.output = Output::Json;
+        config.limit = 2;
+
+        let output = render_with_summary(config);
+
+        // Directory sizes depend on the file system
+        let output: String = output
+            .split(",\"size\":")
+            .enumerate()
+            .map(|(i, part)| {
+                if i == 0 {
+                    part
+                } else {
+                    part.trim_start_matches(char::is_numeric)
+                }
+            })
+            .collect();
+
+        let expected = r#"[
+  {"type":"directory","name":"test","path":"test","contents":[
+    {"type":"directory","name":"a","path":"test/a","contents":[
+      {"type":"directory","name":"b","path":"test/a/b"}
+    ]},
+    {"type":"file","name":"c.txt","path":"test/c.txt","exec":false},
+    {"type":"directory","name":"d","path":"test/d","contents":[
+      {"type":"directory","name":"e","path":"test/d/e"},
+      {"type":"file","name":"h.txt","path":"test/d/h.txt","exec":false}
+    ]},
+    {"type":"file","name":"d.txt","path":"test/d.txt","exec":false}
+  ]}
+,
+  {"type":"report","directories":4,"files":3,"executables":0,"symlinks":0,"broken_symlinks":0,"block_devices":0,"char_devices":0,"pipes":0,"sockets":0,"special":0,"bytes":0,"skipped":0,"errors":0}
+]
+"#;
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn xml_test() {
+        let mut config = get_default();
+        config.output = Output::Xml;
+        config.limit = 1;
+
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tree>
+  <directory name="test">
+    <directory name="a"></directory>
+    <file name="c.txt"></file>
+    <directory name="d"></directory>
+    <file name="d.txt"></file>
+  </directory>
+  <report>
+    <directories>2</directories>
+    <files>2</files>
+  </report>
+</tree>
+"#;
+        assert_eq!(render_with_summary(config), expected);
+    }
+
+    #[test]
+    fn html_test() {
+        let mut config = get_default();
+        config.output = Output::Html;
+        config.base_href = Some(String::from("https://example.com/files/"));
+
+        let output = render_with_summary(config);
+        assert!(output.starts_with("<!DOCTYPE html>"));
+        assert!(output.contains(r#"<a href="https://example.com/files/d/e/f/">f</a>"#));
+        assert!(output.contains(
+            r#"<li class="file"><a href="https://example.com/files/d/h.txt">h.txt</a></li>"#
+        ));
+        assert!(output.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn gitignore_test() {
+        let tmp = TempDir::new("gitignore");
+        tmp.file(".git/info/exclude", "excluded.txt\n");
+        tmp.file(".gitignore", "*.log\n!keep.log\nbuild/\n");
+        tmp.file("a.log", "");
+        tmp.file("keep.log", "");
+        tmp.file("excluded.txt", "");
+        tmp.file("build/out.txt", "");
+        // Only directories are matched by build/
+        tmp.file("src/build", "");
+        tmp.file("src/.gitignore", "*.rs\n");
+        tmp.file("src/.ignore", "!main.rs\n");
+        tmp.file("src/lib.rs", "");
+        tmp.file("src/main.rs", "");
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+
+        let expected = "├── keep.log
+└── src
+    ├── build
+    └── main.rs
+";
+        assert_eq!(render_below_root(config), expected);
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.no_gitignore = true;
+
+        let expected = "├── a.log
+├── build
+│   └── out.txt
+├── excluded.txt
+├── keep.log
+└── src
+    ├── build
+    ├── lib.rs
+    └── main.rs
+";
+        assert_eq!(render_below_root(config), expected);
+    }
+
+    #[test]
+    fn gitignore_outside_work_tree_test() {
+        let tmp = TempDir::new("gitignore-no-git");
+        tmp.file(".gitignore", "*.log\n");
+        tmp.file("a.log", "");
+        tmp.dir("b");
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        assert_eq!(render_below_root(config), "├── a.log\n└── b\n");
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.gitignore = true;
+        assert_eq!(render_below_root(config), "└── b\n");
+    }
+
+    #[test]
+    fn pattern_test() {
+        let mut config = get_default();
+        config.include = vec!["*.txt".parse().unwrap()];
+        config.exclude = vec!["d.txt".parse().unwrap()];
+
+        let expected = "test
+├── a
+│   └── b
+├── c.txt
+└── d
+    ├── e
+    │   └── f
+    │       └── g
+    └── h.txt
+";
+        assert_eq!(render(config), expected);
+    }
+
+    #[test]
+    fn exclude_directory_test() {
+        let mut config = get_default();
+        config.exclude = vec!["d*".parse().unwrap()];
+
+        let expected = "test
+├── a
+│   └── b
+└── c.txt
+";
+        assert_eq!(render(config), expected);
+    }
+
+    #[test]
+    fn prune_test() {
+        let mut config = get_default();
+        config.include = vec!["*.txt".parse().unwrap()];
+        config.exclude = vec!["d.txt".parse().unwrap()];
+        config.prune = true;
+
+        let expected = "test
+├── c.txt
+└── d
+    └── h.txt
+";
+        assert_eq!(render(config), expected);
+
+        let mut config = get_default();
+        config.include = vec!["*.txt".parse().unwrap()];
+        config.prune = true;
+        assert_eq!(
+            run(config).to_string(),
+            "1 directory and 3 files (9 skipped)"
+        );
+    }
+
+    #[test]
+    fn matchdirs_test() {
+        let mut config = get_default();
+        config.all = true;
+        config.include = vec!["a".parse().unwrap()];
+        config.matchdirs = true;
+        config.prune = true;
+
+        let expected = "test
+└── a
+    └── b
+        └── .gitkeep
+";
+        assert_eq!(render(config), expected);
+    }
+
+    #[test]
+    fn dirs_first_test() {
+        let mut config = get_default();
+        config.dirs_first = true;
+        config.limit = 2;
+
+        let expected = "test
+├── a
+│   └── b
+├── d
+│   ├── e
+│   └── h.txt
+├── c.txt
+└── d.txt
+";
+        assert_eq!(render(config), expected);
+    }
+
+    #[test]
+    fn reverse_dirs_last_test() {
+        let mut config = get_default();
+        config.reverse = true;
+        config.dirs_last = true;
+        config.limit = 2;
+
+        let expected = "test
+├── d.txt
+├── c.txt
+├── d
+│   ├── h.txt
+│   └── e
+└── a
+    └── b
+";
+        assert_eq!(render(config), expected);
+    }
+
+    #[test]
+    fn sort_version_test() {
+        let tmp = TempDir::new("sort-version");
+        for name in ["file10", "file9", "File2", "file1"] {
+            tmp.file(name, "");
+        }
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.sort = SortBy::Version;
+        assert_eq!(
+            render_below_root(config),
+            "├── file1\n├── File2\n├── file9\n└── file10\n"
+        );
+    }
+
+    #[test]
+    fn sort_size_test() {
+        let tmp = TempDir::new("sort-size");
+        tmp.file("small", "a");
+        tmp.file("large", "abc");
+        tmp.file("medium", "ab");
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.sort = SortBy::Size;
+        assert_eq!(
+            render_below_root(config),
+            "├── large\n├── medium\n└── small\n"
+        );
+    }
+
+    #[test]
+    fn du_test() {
+        let tmp = TempDir::new("du");
+        tmp.file("a/b/c/big", &"x".repeat(3000));
+        tmp.file("a/small", "xx");
+        tmp.file("top", "x");
+
+        let dir_size: u64 = ["a", "a/b", "a/b/c"]
+            .iter()
+            .map(|dir| fs::metadata(tmp.path().join(dir)).unwrap().len())
+            .sum();
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.du = true;
+        config.limit = 1;
+
+        // The totals include what lies beyond the depth limit
+        let expected = format!("├── [{:>11}]  a\n└── [          1]  top\n", dir_size + 3002);
+        assert_eq!(render_below_root(config), expected);
+
+        // Followed links to directories count their contents once, as without `--du`
+        std::os::unix::fs::symlink("a", tmp.path().join("link")).unwrap();
+        let bytes = |du| {
+            let mut config = get_default();
+            config.directories = vec![tmp.path().to_path_buf()];
+            config.follow = true;
+            config.du = du;
+            run(config).bytes
+        };
+        assert_eq!(bytes(true), bytes(false));
+    }
+
+    #[test]
+    fn permissions_date_test() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::time::{Duration, SystemTime};
+
+        let tmp = TempDir::new("permissions");
+        tmp.file("script", "");
+        let path = tmp.path().join("script");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o4750)).unwrap();
+        let mid_2001 = SystemTime::UNIX_EPOCH + Duration::from_secs(994_000_000);
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mid_2001)
+            .unwrap();
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.permissions = true;
+        config.timefmt = Some(String::from("%Y"));
+        assert_eq!(render_below_root(config), "└── [-rwsr-x--- 2001]  script\n");
+    }
+
+    #[test]
+    fn non_utf8_test() {
+        use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+        let tmp = TempDir::new("non_utf8");
+        fs::write(tmp.path().join(OsStr::from_bytes(b"caf\xe9\n")), "").unwrap();
+        tmp.file(".hidden", "");
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        assert_eq!(render_below_root(config), "└── caf�?\n");
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.escape = true;
+        assert_eq!(render_below_root(config), "└── caf\\xe9\\n\n");
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.literal = true;
+        assert_eq!(render_below_root(config), "└── caf�\n\n");
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.output = Output::Json;
+        assert!(render(config).contains(r#""name":"caf\udce9\n""#));
+    }
+
+    #[test]
+    fn error_test() {
+        let tmp = TempDir::new("error");
+        tmp.file("locked/secret", "");
+        tmp.file("z", "");
+
+        // Permissions do not stop root, so fake what reading `locked` would have reported
+        let tree = Tree::new(get_default());
+        let node = |name: &str, descend, errors| Node {
+            file: File::from_path(&tmp.path().join(name)).unwrap(),
+            descend,
+            children: Vec::new(),
+            elided: None,
+            errors,
+        };
+        let denied = Error::read_dir(io::Error::from_raw_os_error(13));
+        let root = Node {
+            children: vec![node("locked", true, vec![denied]), node("z", false, vec![])],
+            ..node("", true, vec![])
+        };
+
         let mut output = Vec::new();
-        let expected = "";
+        tree.render(&mut output, &root, 0, true, &mut Vec::new())
+            .unwrap();
+        let expected = format!(
+            "{}\n├── locked\n│   └── [error opening dir: Permission denied]\n└── z\n",
+            tmp.path().file_name().unwrap().to_string_lossy()
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+        let mut stats = Stats::default();
+        let missing = State::new(tmp.path().join("missing"), 0, None, false);
+        let contents = tree.collect(missing, &mut stats);
+        assert!(contents.nodes.is_empty());
+        assert!(matches!(contents.errors[..], [Error::ReadDir { .. }]));
+        assert_eq!(stats.errors, 1);
+    }
+
+    #[test]
+    fn follow_test() {
+        use std::os::unix::fs::symlink;
+
+        let tmp = TempDir::new("follow");
+        tmp.file("b/x", "");
+        tmp.dir("a");
+        symlink("../b", tmp.path().join("a/b")).unwrap();
+        symlink("..", tmp.path().join("a/up")).unwrap();
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        assert_eq!(
+            render_below_root(config),
+            "├── a\n│   ├── b ⇒ ../b\n│   └── up ⇒ ..\n└── b\n    └── x\n"
+        );
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.follow = true;
+        let expected = "├── a
+│   ├── b ⇒ ../b
+│   │   └── x
+│   └── up ⇒ ..
+│       └── [recursive, not followed]
+└── b
+    └── x
+";
+        assert_eq!(render_below_root(config), expected);
+    }
+
+    #[test]
+    fn symlink_test() {
+        use std::os::unix::fs::symlink;
+
+        let tmp = TempDir::new("symlink");
+        tmp.file("c", "");
+        tmp.dir("lib");
+        tmp.dir("sub");
+        symlink("b", tmp.path().join("a")).unwrap();
+        symlink("c", tmp.path().join("b")).unwrap();
+        symlink("nowhere", tmp.path().join("dangling")).unwrap();
+        symlink("y", tmp.path().join("x")).unwrap();
+        symlink("x", tmp.path().join("y")).unwrap();
+        // Only valid relative to the link's directory, not the working directory
+        symlink("../lib", tmp.path().join("sub/lib")).unwrap();
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        let expected = "├── a ⇒ b ⇒ c
+├── b ⇒ c
+├── c
+├── dangling ⇒ nowhere [broken]
+├── lib
+├── sub
+│   └── lib ⇒ ../lib
+├── x ⇒ y ⇒ x [loop]
+└── y ⇒ x ⇒ y [loop]
+";
+        assert_eq!(render_below_root(config), expected);
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.limit = 1;
+        config.resolve = true;
+        let canonical = fs::canonicalize(tmp.path()).unwrap();
+        let output = render_below_root(config);
+        assert!(output.starts_with(&format!("├── a ⇒ {}\n", canonical.join("c").display())));
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        let stats = run(config);
+        assert_eq!((stats.symlinks, stats.broken_symlinks), (3, 3));
+    }
+
+    #[test]
+    fn parallel_test() {
+        let tmp = TempDir::new("parallel");
+        for i in 0..20 {
+            for j in 0..i % 4 {
+                tmp.file(
+                    &format!("dir{}/sub{}/file{}", i, j, i * j),
+                    &"x".repeat(i * j),
+                );
+            }
+            tmp.file(&format!("file{}", i), "");
+        }
+
+        let config = |threads| {
+            let mut config = get_default();
+            config.directories = vec![tmp.path().to_path_buf()];
+            config.threads = threads;
+            config.du = true;
+            config
+        };
+        assert_eq!(
+            render_with_summary(config(4)),
+            render_with_summary(config(1))
+        );
+        assert_eq!(run(config(4)), run(config(1)));
+    }
+
+    #[test]
+    fn unsorted_test() {
+        let config = |unsorted| {
+            let mut config = get_default();
+            config.all = true;
+            config.unsorted = unsorted;
+            config.sort = SortBy::None;
+            config
+        };
+        // Both list entries in the order they are read
+        assert_eq!(
+            render_with_summary(config(true)),
+            render_with_summary(config(false))
+        );
+
+        let mut json = config(true);
+        json.output = Output::Json;
+        let mut expected = config(false);
+        expected.output = Output::Json;
+        assert_eq!(render_with_summary(json), render_with_summary(expected));
+    }
+
+    #[test]
+    fn max_entries_test() {
+        let tmp = TempDir::new("max_entries");
+        for name in ["a/x", "b", "c/y", "d", "e"] {
+            tmp.file(name, "xx");
+        }
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.max_entries = Some(2);
+        let expected = "├── a
+│   └── x
+├── b
+└── … 3 more entries (1 dir, 2 files)
+";
+        assert_eq!(render_below_root(config), expected);
+
+        // Elided entries are still counted
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.max_entries = Some(2);
+        let stats = run(config);
+        assert_eq!((stats.directories, stats.files), (2, 5));
+    }
+
+    #[test]
+    fn filelimit_test() {
+        let tmp = TempDir::new("filelimit");
+        tmp.file("big/a", "");
+        tmp.file("big/b", "");
+        tmp.file("big/c", "");
+        tmp.file("small/a", "");
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.filelimit = Some(2);
+        let expected = "├── big
+│   └── [3 entries exceeds filelimit, not opening dir]
+└── small
+    └── a
+";
+        assert_eq!(render_below_root(config), expected);
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.filelimit = Some(2);
+        assert_eq!(run(config).errors, 0);
+    }
+
+    #[test]
+    fn full_path_test() {
+        let mut config = get_default();
+        config.full_path = true;
+        config.noindent = true;
+        config.sort = SortBy::Version;
+        config.include = vec!["*.txt".parse().unwrap()];
+        let expected = "test
+test/a
+test/a/b
+test/c.txt
+test/d
+test/d/e
+test/d/e/f
+test/d/e/f/g
+test/d/h.txt
+test/d.txt
+";
+        assert_eq!(render(config), expected);
+
+        let mut config = get_default();
+        config.absolute = true;
+        config.limit = 1;
+        let root = fs::canonicalize("test").unwrap();
+        let expected = format!("{}\n├── {}\n", root.display(), root.join("a").display());
+        assert!(render(config).starts_with(&expected));
+    }
+
+    #[test]
+    fn roots_test() {
+        let tmp = TempDir::new("roots");
+        tmp.file("a/src/lib.rs", "");
+        tmp.file("b/src/main.rs", "");
+        tmp.file("README", "");
+
+        let mut config = get_default();
+        config.directories = ["a/src", "b/src", "missing", "README"]
+            .iter()
+            .map(|root| tmp.path().join(root))
+            .collect();
+        // Roots are shown as given, so the two `src` can be told apart
+        let expected = format!(
+            "{0}/a/src
+└── lib.rs
+{0}/b/src
+└── main.rs
+[error reading {0}/missing: No such file or directory]
+{0}/README
+0 directories and 3 files (1 error)
+",
+            tmp.path().display()
+        );
+        assert_eq!(render_with_summary(config), expected);
+    }
+
+    #[test]
+    fn human_size_test() {
+        let tmp = TempDir::new("human");
+        tmp.file("kilo", &"x".repeat(2048));
+        tmp.file("byte", "x");
+
+        let mut config = get_default();
+        config.directories = vec![tmp.path().to_path_buf()];
+        config.human = true;
+        assert_eq!(
+            render_below_root(config),
+            "├── [   1]  byte\n└── [2.0K]  kilo\n"
+        );
+    }
+
+    #[test]
+    fn color_test() {
+        let mut config = get_default();
+        config.color = ColorChoice::Always;
+        config.limit = 1;
 
-        // tree(&mut output);
+        let output = render(config);
 
-        let actual = String::from_utf8(output).expect("Not UTF-8");
-        assert_eq!(expected, actual);
+        // At least the directories are styled
+        assert!(output.contains("\u{1b}["));
+        assert!(!ColorChoice::Never.enabled());
+        assert!(ColorChoice::Always.enabled());
     }
 }
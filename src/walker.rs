@@ -0,0 +1,297 @@
+use std::{path::PathBuf, vec};
+
+use crate::{
+    error::Error,
+    file::File,
+    filter::Pattern,
+    sort::{DirOrder, SortBy},
+    stats::Stats,
+    tree::{Node, Predicate, Tree},
+    Config,
+};
+
+/// Builds a walk over a directory tree with the same options as `mtree`
+///
+/// ```no_run
+/// use mtree::TreeWalker;
+///
+/// let walk = TreeWalker::new("src")
+///     .max_depth(2)
+///     .hidden(true)
+///     .filter(|file| !file.name().to_string_lossy().ends_with(".bak"))
+///     .walk()?;
+/// for entry in walk {
+///     println!("{}{:?}", "  ".repeat(entry.depth as usize), entry.file.map(|f| f.path().to_owned()));
+/// }
+/// # Ok::<(), mtree::Error>(())
+/// ```
+pub struct TreeWalker {
+    config: Config,
+    predicate: Option<Predicate>,
+}
+
+impl TreeWalker {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            config: Config {
+                directories: vec![root.into()],
+                threads: 1,
+                ..Config::default()
+            },
+            predicate: None,
+        }
+    }
+
+    /// How many levels below the root are read, 5 by default
+    pub fn max_depth(mut self, depth: u32) -> Self {
+        self.config.limit = depth;
+        self
+    }
+
+    /// Whether entries starting with `.` are listed
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.config.all = hidden;
+        self
+    }
+
+    /// Whether only directories are listed
+    pub fn directories_only(mut self, directories_only: bool) -> Self {
+        self.config.directory_only = directories_only;
+        self
+    }
+
+    /// Whether git's ignore rules apply, by default only inside a git work tree
+    pub fn gitignore(mut self, gitignore: bool) -> Self {
+        self.config.gitignore = gitignore;
+        self.config.no_gitignore = !gitignore;
+        self
+    }
+
+    /// Only lists files matching one of the included patterns, like `-P`
+    pub fn include(mut self, pattern: Pattern) -> Self {
+        self.config.include.push(pattern);
+        self
+    }
+
+    /// Leaves out files and directories matching the pattern, like `-I`
+    pub fn exclude(mut self, pattern: Pattern) -> Self {
+        self.config.exclude.push(pattern);
+        self
+    }
+
+    /// Only lists the files `predicate` accepts, applied after all other filters
+    ///
+    /// Directories which are not accepted are not descended into.
+    pub fn filter(mut self, predicate: impl Fn(&File) -> bool + Send + Sync + 'static) -> Self {
+        self.predicate = Some(Box::new(predicate));
+        self
+    }
+
+    /// Whether directories which are empty after filtering are left out
+    pub fn prune(mut self, prune: bool) -> Self {
+        self.config.prune = prune;
+        self
+    }
+
+    pub fn sort(mut self, by: SortBy, reverse: bool, dirs: DirOrder) -> Self {
+        self.config.sort = by;
+        self.config.reverse = reverse;
+        self.config.dirs_first = dirs == DirOrder::First;
+        self.config.dirs_last = dirs == DirOrder::Last;
+        self
+    }
+
+    /// Whether symlinks to directories are descended into
+    pub fn follow(mut self, follow: bool) -> Self {
+        self.config.follow = follow;
+        self
+    }
+
+    /// How many threads read directories, 1 by default and 0 for one per CPU
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.config.threads = threads;
+        self
+    }
+
+    /// Reads the tree, which fails only if the root cannot be read
+    ///
+    /// The whole tree is read into memory first, as it has to be sorted and filtered, and the
+    /// entries are then taken from it one by one.
+    pub fn walk(self) -> Result<Walk, Error> {
+        let root = self.config.directories[0].clone();
+        let mut tree = Tree::reader(self.config);
+        if let Some(predicate) = self.predicate {
+            tree = tree.with_predicate(predicate);
+        }
+
+        let (node, stats) = tree.build(&root)?;
+        Ok(Walk {
+            root: Some(node),
+            stack: Vec::new(),
+            ancestors: Vec::new(),
+            stats,
+        })
+    }
+}
+
+/// An entry of a tree, in the order of display
+pub struct Entry {
+    /// The root has depth 0
+    pub depth: u32,
+    /// Whether this is the last entry of its directory
+    pub is_last: bool,
+    /// Whether each ancestor below the root was the last entry of its directory
+    pub ancestors: Vec<bool>,
+    /// An error if an entry of the parent directory could not be read
+    pub file: Result<File, Error>,
+}
+
+/// The entries of a tree read by [`TreeWalker::walk`]
+pub struct Walk {
+    /// Until it is returned as the first entry
+    root: Option<Node>,
+    /// What is left of each directory being walked
+    stack: Vec<Level>,
+    /// Whether each directory on `stack` but the root was the last entry of its parent
+    ancestors: Vec<bool>,
+    stats: Stats,
+}
+
+/// The children of a directory followed by the errors reading them
+struct Level {
+    children: vec::IntoIter<Node>,
+    errors: vec::IntoIter<Error>,
+}
+
+impl Walk {
+    /// The counts of the whole tree
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Returns `node` as an entry and continues with its children
+    fn enter(&mut self, node: Node, is_last: bool) -> Entry {
+        let depth = self.stack.len() as u32;
+        let entry = Entry {
+            depth,
+            is_last,
+            ancestors: self.ancestors.clone(),
+            file: Ok(node.file),
+        };
+
+        // The root is not drawn with connectors
+        if depth > 0 {
+            self.ancestors.push(is_last);
+        }
+        self.stack.push(Level {
+            children: node.children.into_iter(),
+            errors: node.errors.into_iter(),
+        });
+        entry
+    }
+}
+
+impl Iterator for Walk {
+    type Item = Entry;
+
+    fn next(&mut self) -> Option<Entry> {
+        if let Some(root) = self.root.take() {
+            return Some(self.enter(root, true));
+        }
+
+        loop {
+            let depth = self.stack.len() as u32;
+            let level = self.stack.last_mut()?;
+            if let Some(child) = level.children.next() {
+                let is_last = level.children.len() + level.errors.len() == 0;
+                return Some(self.enter(child, is_last));
+            }
+            if let Some(error) = level.errors.next() {
+                return Some(Entry {
+                    depth,
+                    is_last: level.errors.len() == 0,
+                    ancestors: self.ancestors.clone(),
+                    file: Err(error),
+                });
+            }
+
+            self.stack.pop();
+            if !self.stack.is_empty() {
+                self.ancestors.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{details::Details, file::Appearance, icons::IconSet, Formatter};
+    use pretty_assertions::assert_eq;
+
+    fn walker() -> TreeWalker {
+        TreeWalker::new("test")
+    }
+
+    #[test]
+    fn walk_test() {
+        let walk = walker().max_depth(1).walk().unwrap();
+        assert_eq!(walk.stats().directories, 2);
+
+        let entries: Vec<_> = walk
+            .map(|e| {
+                let name = e.file.unwrap().name().to_string_lossy().into_owned();
+                (e.depth, e.is_last, name)
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (0, true, String::from("test")),
+                (1, false, String::from("a")),
+                (1, false, String::from("c.txt")),
+                (1, false, String::from("d")),
+                (1, true, String::from("d.txt")),
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_test() {
+        let walk = walker()
+            .hidden(true)
+            .filter(|file| file.name() != ".gitkeep" && file.name() != "e")
+            .walk()
+            .unwrap();
+        let entries: Vec<_> = walk.collect();
+        let depths: Vec<_> = entries.iter().map(|e| e.depth).collect();
+        assert_eq!(depths, vec![0, 1, 2, 1, 1, 2, 1]);
+        // h.txt, below d which is followed by d.txt
+        assert_eq!(entries[5].ancestors, vec![false]);
+        assert!(entries[5].is_last);
+    }
+
+    #[test]
+    fn formatter_test() {
+        let appearance = Appearance {
+            icons: IconSet::None.into(),
+            ..Appearance::default()
+        };
+        let formatter = Formatter::new(appearance, Details::default(), true);
+
+        let mut output = Vec::new();
+        for entry in walker().max_depth(2).walk().unwrap() {
+            formatter.write(&mut output, &entry).unwrap();
+        }
+        let expected = "test
+├── a
+│   └── b
+├── c.txt
+├── d
+│   ├── e
+│   └── h.txt
+└── d.txt
+";
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+}